@@ -5,13 +5,12 @@ from tikv_client.asynchronous import TransactionClient
 async def main():
     client = await TransactionClient.connect(["127.0.0.1:2379"])
 
-    txn = await client.begin(pessimistic=True)
-    await txn.put(b"k1", b"v1")
-    await txn.put(b"k2", b"v2")
-    await txn.put(b"k3", b"v3")
-    await txn.put(b"k4", b"v4")
-    await txn.put(b"k5", b"v5")
-    await txn.commit()
+    async with client.begin(pessimistic=True) as txn:
+        await txn.put(b"k1", b"v1")
+        await txn.put(b"k2", b"v2")
+        await txn.put(b"k3", b"v3")
+        await txn.put(b"k4", b"v4")
+        await txn.put(b"k5", b"v5")
 
     snapshot = client.snapshot(await client.current_timestamp(), pessimistic=True)
     print(await snapshot.get(b"k3"))
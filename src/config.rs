@@ -0,0 +1,272 @@
+// Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::time::Duration;
+
+use pyo3::prelude::*;
+use pyo3::types::*;
+
+use crate::error::InvalidArgumentError;
+
+/// A backoff policy used to retry requests failing with region or lock errors.
+#[pyclass]
+#[derive(Clone)]
+pub struct Backoff {
+    pub inner: tikv_client::Backoff,
+}
+
+#[pymethods]
+impl Backoff {
+    #[staticmethod]
+    pub fn no_backoff() -> Self {
+        Backoff {
+            inner: tikv_client::Backoff::no_backoff(),
+        }
+    }
+
+    #[staticmethod]
+    pub fn no_jitter(base_delay_ms: u64, max_delay_ms: u64, max_attempts: u32) -> Self {
+        Backoff {
+            inner: tikv_client::Backoff::no_jitter_backoff(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            ),
+        }
+    }
+
+    #[staticmethod]
+    pub fn full_jitter(base_delay_ms: u64, max_delay_ms: u64, max_attempts: u32) -> Self {
+        Backoff {
+            inner: tikv_client::Backoff::full_jitter_backoff(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            ),
+        }
+    }
+
+    #[staticmethod]
+    pub fn equal_jitter(base_delay_ms: u64, max_delay_ms: u64, max_attempts: u32) -> Self {
+        Backoff {
+            inner: tikv_client::Backoff::equal_jitter_backoff(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            ),
+        }
+    }
+
+    #[staticmethod]
+    pub fn decorrelated_jitter(base_delay_ms: u64, max_delay_ms: u64, max_attempts: u32) -> Self {
+        Backoff {
+            inner: tikv_client::Backoff::decorrelated_jitter_backoff(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            ),
+        }
+    }
+
+    /// Returns the delay in seconds before the next attempt, or `None` once
+    /// `max_attempts` delays have been returned.
+    pub fn next_delay(&mut self) -> Option<f64> {
+        self.inner
+            .next_delay_duration()
+            .map(|delay| delay.as_secs_f64())
+    }
+
+    pub fn __copy__(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl Backoff {
+    /// Accepts either a `Backoff` or a dict such as
+    /// `{"kind": "full_jitter", "base_delay_ms": 2, "max_delay_ms": 500, "max_attempts": 10}`.
+    fn from_py(value: &PyAny) -> PyResult<Self> {
+        if let Ok(backoff) = value.extract::<Backoff>() {
+            return Ok(backoff);
+        }
+        let dict = value.downcast::<PyDict>()?;
+        let kind: String = get_item(dict, "kind")?.unwrap_or_else(|| "full_jitter".to_owned());
+        if kind == "no_backoff" {
+            return Ok(Backoff::no_backoff());
+        }
+        let base_delay_ms = get_item(dict, "base_delay_ms")?.unwrap_or(2);
+        let max_delay_ms = get_item(dict, "max_delay_ms")?.unwrap_or(500);
+        let max_attempts = get_item(dict, "max_attempts")?.unwrap_or(10);
+        match kind.as_str() {
+            "no_jitter" => Ok(Backoff::no_jitter(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            )),
+            "full_jitter" => Ok(Backoff::full_jitter(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            )),
+            "equal_jitter" => Ok(Backoff::equal_jitter(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            )),
+            "decorrelated_jitter" => Ok(Backoff::decorrelated_jitter(
+                base_delay_ms,
+                max_delay_ms,
+                max_attempts,
+            )),
+            _ => Err(InvalidArgumentError::new_err(format!(
+                "unknown backoff kind `{}`",
+                kind
+            ))),
+        }
+    }
+}
+
+/// Options shared by `RawClient.connect()` and `TransactionClient.connect()`.
+#[pyclass]
+#[derive(Clone, Default)]
+pub struct Config {
+    /// Timeout of each gRPC request in seconds.
+    #[pyo3(get, set)]
+    pub timeout: Option<f64>,
+    #[pyo3(get, set)]
+    pub ca_path: Option<String>,
+    #[pyo3(get, set)]
+    pub cert_path: Option<String>,
+    #[pyo3(get, set)]
+    pub key_path: Option<String>,
+    /// Backoff of requests failing with region errors.
+    #[pyo3(get, set)]
+    pub region_backoff: Option<Backoff>,
+    /// Backoff of transactional requests blocked by locks.
+    #[pyo3(get, set)]
+    pub lock_backoff: Option<Backoff>,
+}
+
+const FIELDS: &[&str] = &[
+    "timeout",
+    "ca_path",
+    "cert_path",
+    "key_path",
+    "region_backoff",
+    "lock_backoff",
+];
+
+#[pymethods]
+impl Config {
+    #[new]
+    #[pyo3(signature=(timeout=None, ca_path=None, cert_path=None, key_path=None, region_backoff=None, lock_backoff=None))]
+    pub fn new(
+        timeout: Option<f64>,
+        ca_path: Option<String>,
+        cert_path: Option<String>,
+        key_path: Option<String>,
+        region_backoff: Option<Backoff>,
+        lock_backoff: Option<Backoff>,
+    ) -> Self {
+        Config {
+            timeout,
+            ca_path,
+            cert_path,
+            key_path,
+            region_backoff,
+            lock_backoff,
+        }
+    }
+
+    #[staticmethod]
+    pub fn from_dict(dict: &PyDict) -> PyResult<Self> {
+        for key in dict.keys() {
+            let key: &str = key.extract()?;
+            if !FIELDS.contains(&key) {
+                return Err(InvalidArgumentError::new_err(format!(
+                    "unknown config option `{}`",
+                    key
+                )));
+            }
+        }
+        let backoff = |name| -> PyResult<Option<Backoff>> {
+            dict.get_item(name)
+                .filter(|value| !value.is_none())
+                .map(Backoff::from_py)
+                .transpose()
+        };
+        Ok(Config {
+            timeout: get_item(dict, "timeout")?,
+            ca_path: get_item(dict, "ca_path")?,
+            cert_path: get_item(dict, "cert_path")?,
+            key_path: get_item(dict, "key_path")?,
+            region_backoff: backoff("region_backoff")?,
+            lock_backoff: backoff("lock_backoff")?,
+        })
+    }
+
+    /// Reads `<prefix>TIMEOUT`, `<prefix>CA_PATH`, `<prefix>CERT_PATH` and
+    /// `<prefix>KEY_PATH` from the environment.
+    #[staticmethod]
+    #[pyo3(signature=(prefix="TIKV_"))]
+    pub fn from_env(prefix: &str) -> PyResult<Self> {
+        let var = |name: &str| std::env::var(format!("{}{}", prefix, name)).ok();
+        let timeout = var("TIMEOUT")
+            .map(|timeout| {
+                timeout.parse().map_err(|_| {
+                    InvalidArgumentError::new_err(format!(
+                        "invalid timeout `{}` in {}TIMEOUT",
+                        timeout, prefix
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(Config {
+            timeout,
+            ca_path: var("CA_PATH"),
+            cert_path: var("CERT_PATH"),
+            key_path: var("KEY_PATH"),
+            region_backoff: None,
+            lock_backoff: None,
+        })
+    }
+
+    /// Reads the options from the top-level table of a TOML file.
+    #[staticmethod]
+    pub fn from_toml(py: Python, path: &str) -> PyResult<Self> {
+        let toml = py.import("tomllib").or_else(|_| py.import("tomli"))?;
+        let file = py.import("builtins")?.call_method1("open", (path, "rb"))?;
+        let parsed = toml.call_method1("load", (file,));
+        file.call_method0("close")?;
+        Config::from_dict(parsed?.downcast()?)
+    }
+}
+
+impl Config {
+    pub fn to_tikv_config(&self) -> PyResult<tikv_client::Config> {
+        let mut config = tikv_client::Config::default();
+        if let Some(timeout) = self.timeout {
+            let timeout = Duration::try_from_secs_f64(timeout).map_err(|_| {
+                InvalidArgumentError::new_err("`timeout` must be a non-negative number")
+            })?;
+            config = config.with_timeout(timeout);
+        }
+        match (&self.ca_path, &self.cert_path, &self.key_path) {
+            (Some(ca_path), Some(cert_path), Some(key_path)) => {
+                config = config.with_security(ca_path, cert_path, key_path);
+            }
+            (None, None, None) => {}
+            _ => {
+                return Err(InvalidArgumentError::new_err(
+                    "`ca_path`, `cert_path` and `key_path` must be provided together",
+                ))
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn get_item<'a, T: FromPyObject<'a>>(dict: &'a PyDict, key: &str) -> PyResult<Option<T>> {
+    match dict.get_item(key) {
+        Some(value) if !value.is_none() => value.extract().map(Some),
+        _ => Ok(None),
+    }
+}
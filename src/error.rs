@@ -0,0 +1,246 @@
+// Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use tikv_client::Error;
+
+create_exception!(
+    tikv_client,
+    TiKVError,
+    PyException,
+    "Base class of all errors raised by the TiKV client.\n\n\
+     Attributes: `is_retryable` tells whether running the operation or transaction again may \
+     succeed, and `is_undetermined` whether a commit may or may not have taken effect."
+);
+create_exception!(
+    tikv_client,
+    TiKVKeyError,
+    TiKVError,
+    "TiKV refused to read or write a key."
+);
+create_exception!(
+    tikv_client,
+    WriteConflictError,
+    TiKVKeyError,
+    "The key has been written by another transaction since this transaction started.\n\n\
+     Attributes: `key`, `primary`, `start_ts`, `conflict_ts` and `conflict_commit_ts`."
+);
+create_exception!(
+    tikv_client,
+    KeyExistsError,
+    TiKVKeyError,
+    "The key to be inserted already exists.\n\nAttributes: `key`."
+);
+create_exception!(
+    tikv_client,
+    DeadlockError,
+    TiKVKeyError,
+    "Acquiring the pessimistic lock would cause a deadlock."
+);
+create_exception!(
+    tikv_client,
+    RegionError,
+    TiKVError,
+    "The region serving the request has moved, split or lost its leader."
+);
+create_exception!(
+    tikv_client,
+    RequestTimeoutError,
+    TiKVError,
+    "The request did not finish within the configured timeout."
+);
+create_exception!(
+    tikv_client,
+    GrpcError,
+    TiKVError,
+    "The request failed at the gRPC transport layer."
+);
+create_exception!(
+    tikv_client,
+    UndeterminedError,
+    TiKVError,
+    "Whether the transaction has been committed or not is unknown."
+);
+create_exception!(
+    tikv_client,
+    TransactionError,
+    TiKVError,
+    "The operation is not allowed in the current state of the transaction."
+);
+create_exception!(
+    tikv_client,
+    UnsupportedModeError,
+    TiKVError,
+    "The operation is not supported by the atomic or non-atomic mode of the raw client."
+);
+create_exception!(
+    tikv_client,
+    ClientClosedError,
+    TiKVError,
+    "The client has been closed."
+);
+create_exception!(
+    tikv_client,
+    InvalidArgumentError,
+    TiKVError,
+    "An argument passed to the client is invalid."
+);
+
+pub fn add_exceptions(py: Python, m: &PyModule) -> PyResult<()> {
+    // Defaults for errors that are not raised through `to_py_execption`.
+    let base = py.get_type::<TiKVError>();
+    base.setattr("is_retryable", false)?;
+    base.setattr("is_undetermined", false)?;
+    m.add("TiKVError", py.get_type::<TiKVError>())?;
+    m.add("TiKVKeyError", py.get_type::<TiKVKeyError>())?;
+    m.add("WriteConflictError", py.get_type::<WriteConflictError>())?;
+    m.add("KeyExistsError", py.get_type::<KeyExistsError>())?;
+    m.add("DeadlockError", py.get_type::<DeadlockError>())?;
+    m.add("RegionError", py.get_type::<RegionError>())?;
+    m.add("RequestTimeoutError", py.get_type::<RequestTimeoutError>())?;
+    m.add("GrpcError", py.get_type::<GrpcError>())?;
+    m.add("UndeterminedError", py.get_type::<UndeterminedError>())?;
+    m.add("TransactionError", py.get_type::<TransactionError>())?;
+    m.add(
+        "UnsupportedModeError",
+        py.get_type::<UnsupportedModeError>(),
+    )?;
+    m.add("ClientClosedError", py.get_type::<ClientClosedError>())?;
+    m.add(
+        "InvalidArgumentError",
+        py.get_type::<InvalidArgumentError>(),
+    )?;
+    Ok(())
+}
+
+pub fn to_py_execption(err: Error) -> PyErr {
+    let message = err.to_string();
+    let cause = root_cause(&err);
+    let py_err = match cause {
+        Error::KeyError(key_error) if key_error.conflict.is_some() => {
+            WriteConflictError::new_err(message)
+        }
+        Error::KeyError(key_error) if key_error.already_exist.is_some() => {
+            KeyExistsError::new_err(message)
+        }
+        Error::KeyError(key_error) if key_error.deadlock.is_some() => {
+            DeadlockError::new_err(message)
+        }
+        Error::KeyError(_) => TiKVKeyError::new_err(message),
+        Error::DuplicateKeyInsertion => KeyExistsError::new_err(message),
+        Error::RegionError(_)
+        | Error::RegionForKeyNotFound { .. }
+        | Error::RegionForRangeNotFound { .. }
+        | Error::RegionNotFoundInResponse { .. }
+        | Error::LeaderNotFound { .. } => RegionError::new_err(message),
+        Error::GrpcAPI(status) if status.code() == tonic::Code::DeadlineExceeded => {
+            RequestTimeoutError::new_err(message)
+        }
+        Error::GrpcAPI(_) | Error::Grpc(_) => GrpcError::new_err(message),
+        Error::UndeterminedError(_) => UndeterminedError::new_err(message),
+        Error::InvalidTransactionType
+        | Error::OperationAfterCommitError
+        | Error::OnePcFailure
+        | Error::NoPrimaryKey => TransactionError::new_err(message),
+        Error::UnsupportedMode => UnsupportedModeError::new_err(message),
+        Error::ColumnFamilyError(_) | Error::MaxScanLimitExceeded { .. } => {
+            InvalidArgumentError::new_err(message)
+        }
+        _ => TiKVError::new_err(message),
+    };
+    Python::with_gil(|py| match set_attributes(py, &py_err, cause) {
+        Ok(()) => py_err,
+        Err(err) => err,
+    })
+}
+
+/// Fills in the key of a `KeyExistsError` raised for a key that was already
+/// inserted in the transaction buffer, for which the Rust client reports no key.
+pub fn with_key(err: PyErr, key: &[u8]) -> PyErr {
+    Python::with_gil(|py| {
+        let value = err.value(py);
+        let result: PyResult<()> = try {
+            if err.is_instance_of::<KeyExistsError>(py) && value.getattr("key")?.is_none() {
+                value.setattr("key", PyBytes::new(py, key))?;
+            }
+        };
+        match result {
+            Ok(()) => err,
+            Err(err) => err,
+        }
+    })
+}
+
+fn set_attributes(py: Python, py_err: &PyErr, cause: &Error) -> PyResult<()> {
+    let value = py_err.value(py);
+    let is_undetermined = matches!(cause, Error::UndeterminedError(_));
+    value.setattr("is_retryable", is_retryable(cause))?;
+    value.setattr("is_undetermined", is_undetermined)?;
+    if py_err.is_instance_of::<WriteConflictError>(py) {
+        let conflict = match cause {
+            Error::KeyError(key_error) => key_error.conflict.as_ref(),
+            _ => None,
+        };
+        value.setattr("key", conflict.map(|c| PyBytes::new(py, &c.key)))?;
+        value.setattr("primary", conflict.map(|c| PyBytes::new(py, &c.primary)))?;
+        value.setattr("start_ts", conflict.map(|c| c.start_ts))?;
+        value.setattr("conflict_ts", conflict.map(|c| c.conflict_ts))?;
+        value.setattr("conflict_commit_ts", conflict.map(|c| c.conflict_commit_ts))?;
+    } else if py_err.is_instance_of::<KeyExistsError>(py) {
+        let key = match cause {
+            Error::KeyError(key_error) => key_error.already_exist.as_ref().map(|e| &e.key),
+            _ => None,
+        };
+        value.setattr("key", key.map(|key| PyBytes::new(py, key)))?;
+    }
+    Ok(())
+}
+
+/// Whether an error returned by the Rust client may go away if the
+/// operation is tried again.
+pub fn is_retryable_error(err: &Error) -> bool {
+    is_retryable(root_cause(err))
+}
+
+/// Whether the operation, or the whole transaction for transactional errors,
+/// may succeed if tried again. An undetermined commit is never retryable as
+/// the transaction may already have been committed.
+fn is_retryable(cause: &Error) -> bool {
+    match cause {
+        Error::KeyError(key_error) => {
+            key_error.conflict.is_some()
+                || key_error.deadlock.is_some()
+                || key_error.locked.is_some()
+                || !key_error.retryable.is_empty()
+        }
+        Error::RegionError(_)
+        | Error::RegionForKeyNotFound { .. }
+        | Error::RegionForRangeNotFound { .. }
+        | Error::RegionNotFoundInResponse { .. }
+        | Error::LeaderNotFound { .. }
+        | Error::ResolveLockError(_)
+        | Error::Grpc(_) => true,
+        Error::GrpcAPI(status) => matches!(
+            status.code(),
+            tonic::Code::Unavailable
+                | tonic::Code::DeadlineExceeded
+                | tonic::Code::ResourceExhausted
+                | tonic::Code::Aborted
+        ),
+        _ => false,
+    }
+}
+
+/// Unwraps the errors that the Rust client wraps around the error actually
+/// returned by TiKV, so that it can be classified.
+fn root_cause(err: &Error) -> &Error {
+    match err {
+        Error::ExtractedErrors(errors) | Error::MultipleKeyErrors(errors) => {
+            errors.first().map_or(err, root_cause)
+        }
+        Error::PessimisticLockError { inner, .. } => root_cause(inner),
+        _ => err,
+    }
+}
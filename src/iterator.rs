@@ -0,0 +1,194 @@
+// Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use pyo3::types::*;
+use pyo3::ToPyObject;
+use pyo3_asyncio::tokio::future_into_py;
+use tokio::sync::Mutex;
+use tokio::sync::RwLock;
+
+use crate::error::to_py_execption;
+use crate::error::InvalidArgumentError;
+use crate::utils::*;
+
+pub enum ScanSource {
+    Raw(tikv_client::RawClient),
+    Snapshot(Arc<RwLock<tikv_client::Snapshot>>),
+    Transaction(Arc<RwLock<tikv_client::Transaction>>),
+}
+
+impl ScanSource {
+    async fn scan(
+        &self,
+        range: tikv_client::BoundRange,
+        limit: u32,
+        key_only: bool,
+        reverse: bool,
+    ) -> tikv_client::Result<Vec<tikv_client::KvPair>> {
+        match self {
+            // The raw client cannot scan in reverse.
+            ScanSource::Raw(client) => {
+                debug_assert!(!reverse);
+                if key_only {
+                    Ok(with_empty_values(client.scan_keys(range, limit).await?))
+                } else {
+                    client.scan(range, limit).await
+                }
+            }
+            ScanSource::Snapshot(snapshot) => {
+                let mut snapshot = snapshot.write().await;
+                Ok(match (key_only, reverse) {
+                    (false, false) => snapshot.scan(range, limit).await?.collect(),
+                    (false, true) => snapshot.scan_reverse(range, limit).await?.collect(),
+                    (true, false) => with_empty_values(snapshot.scan_keys(range, limit).await?),
+                    (true, true) => {
+                        with_empty_values(snapshot.scan_keys_reverse(range, limit).await?)
+                    }
+                })
+            }
+            // The transaction merges its own uncommitted mutations into the
+            // results of each batch.
+            ScanSource::Transaction(transaction) => {
+                let mut transaction = transaction.write().await;
+                Ok(match (key_only, reverse) {
+                    (false, false) => transaction.scan(range, limit).await?.collect(),
+                    (false, true) => transaction.scan_reverse(range, limit).await?.collect(),
+                    (true, false) => with_empty_values(transaction.scan_keys(range, limit).await?),
+                    (true, true) => {
+                        with_empty_values(transaction.scan_keys_reverse(range, limit).await?)
+                    }
+                })
+            }
+        }
+    }
+}
+
+fn with_empty_values(keys: impl IntoIterator<Item = tikv_client::Key>) -> Vec<tikv_client::KvPair> {
+    keys.into_iter()
+        .map(|key| tikv_client::KvPair::new(key, Vec::new()))
+        .collect()
+}
+
+struct ScanState {
+    source: ScanSource,
+    start: Option<Vec<u8>>,
+    end: Option<Vec<u8>>,
+    include_start: bool,
+    include_end: bool,
+    batch_size: u32,
+    key_only: bool,
+    reverse: bool,
+    buffer: VecDeque<tikv_client::KvPair>,
+    exhausted: bool,
+}
+
+impl ScanState {
+    /// Fetches the next batch and narrows the remaining range to the keys
+    /// after, or before if reversed, the last one fetched.
+    async fn fetch(&mut self) -> PyResult<()> {
+        let range = to_bound_range(
+            self.start.clone(),
+            self.end.clone(),
+            self.include_start,
+            self.include_end,
+        );
+        let pairs = self
+            .source
+            .scan(range, self.batch_size, self.key_only, self.reverse)
+            .await
+            .map_err(to_py_execption)?;
+        if pairs.len() < self.batch_size as usize {
+            self.exhausted = true;
+        }
+        if let Some(last) = pairs.last() {
+            if self.reverse {
+                self.end = Some(last.key().clone().into());
+                self.include_end = false;
+            } else {
+                self.start = Some(last.key().clone().into());
+                self.include_start = false;
+            }
+        }
+        self.buffer.extend(pairs);
+        Ok(())
+    }
+}
+
+/// Iterates over a key range in ascending or, if reversed, descending key
+/// order, fetching `batch_size` entries at a time.
+///
+/// Yields `(key, value)` tuples, or keys if created by a `*_keys` method.
+#[pyclass]
+pub struct ScanIterator {
+    state: Arc<Mutex<ScanState>>,
+}
+
+impl ScanIterator {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        source: ScanSource,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        batch_size: u32,
+        key_only: bool,
+        reverse: bool,
+    ) -> PyResult<Self> {
+        if batch_size == 0 {
+            return Err(InvalidArgumentError::new_err(
+                "`batch_size` must be greater than 0",
+            ));
+        }
+        Ok(ScanIterator {
+            state: Arc::new(Mutex::new(ScanState {
+                source,
+                start,
+                end,
+                include_start,
+                include_end,
+                batch_size,
+                key_only,
+                reverse,
+                buffer: VecDeque::new(),
+                exhausted: false,
+            })),
+        })
+    }
+}
+
+#[pymethods]
+impl ScanIterator {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'p>(&self, py: Python<'p>) -> PyResult<Option<&'p PyAny>> {
+        let state = self.state.clone();
+        let next = future_into_py(py, async move {
+            let mut state = state.lock().await;
+            if state.buffer.is_empty() && !state.exhausted {
+                state.fetch().await?;
+            }
+            let (key, value) = state
+                .buffer
+                .pop_front()
+                .ok_or_else(|| PyStopAsyncIteration::new_err(()))?
+                .into();
+            let key_only = state.key_only;
+            Ok(Python::with_gil(|py| {
+                let key = PyBytes::new(py, (&key).into());
+                if key_only {
+                    key.to_object(py)
+                } else {
+                    (key, PyBytes::new(py, &value)).to_object(py)
+                }
+            }))
+        })?;
+        Ok(Some(next))
+    }
+}
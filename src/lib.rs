@@ -3,18 +3,26 @@
 #![feature(try_blocks)]
 #![feature(never_type)]
 
+mod config;
+mod error;
+mod iterator;
 mod raw;
+mod timestamp;
 mod transaction;
 mod utils;
 
 use pyo3::prelude::*;
 
 #[pymodule]
-fn tikv_client(_py: Python, m: &PyModule) -> PyResult<()> {
+fn tikv_client(py: Python, m: &PyModule) -> PyResult<()> {
     unsafe {
         pyo3::ffi::PyEval_InitThreads();
     }
+    m.add_class::<config::Config>()?;
+    m.add_class::<config::Backoff>()?;
     m.add_class::<raw::RawClient>()?;
+    m.add_class::<timestamp::Timestamp>()?;
     m.add_class::<transaction::TransactionClient>()?;
+    error::add_exceptions(py, m)?;
     Ok(())
 }
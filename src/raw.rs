@@ -2,48 +2,97 @@
 
 use std::convert::TryInto;
 use std::sync::Arc;
-use std::time::Duration;
 
+use crc::Crc;
+use crc::CRC_64_XZ;
+use futures::future::try_join_all;
 use pyo3::prelude::*;
 use pyo3::types::*;
 use pyo3::ToPyObject;
 use pyo3_asyncio::tokio::future_into_py;
 
+use crate::config::Config;
+use crate::error::to_py_execption;
+use crate::error::ClientClosedError;
+use crate::error::InvalidArgumentError;
+use crate::iterator::ScanIterator;
+use crate::iterator::ScanSource;
 use crate::utils::*;
 
+/// The largest number of pairs a single raw scan may return.
+const CHECKSUM_BATCH_SIZE: u32 = 10240;
+
+const CRC64: Crc<u64> = Crc::<u64>::new(&CRC_64_XZ);
+
 #[pyclass]
 pub struct RawClient {
-    inner: Arc<tikv_client::RawClient>,
+    inner: Option<Arc<tikv_client::RawClient>>,
+    atomic: bool,
 }
 
 #[pymethods]
 impl RawClient {
     #[classmethod]
+    #[pyo3(signature=(pd_endpoints, config=None, atomic=false))]
     pub fn connect<'p>(
         _cls: &PyType,
         py: Python<'p>,
         pd_endpoints: Vec<String>,
-        timeout: Option<f64>,
+        config: Option<Config>,
+        atomic: bool,
     ) -> PyResult<&'p PyAny> {
+        let config = config.unwrap_or_default();
+        let tikv_config = config.to_tikv_config()?;
         future_into_py(py, async move {
-            let mut config = tikv_client::Config::default();
-            if let Some(timeout) = timeout {
-                config = config.with_timeout(Duration::from_secs_f64(timeout));
-            }
-            let inner = tikv_client::RawClient::new_with_config(pd_endpoints, config)
+            let mut inner = tikv_client::RawClient::new_with_config(pd_endpoints, tikv_config)
                 .await
                 .map_err(to_py_execption)?;
+            if let Some(backoff) = config.region_backoff {
+                inner = inner.with_backoff(backoff.inner);
+            }
+            if atomic {
+                inner = inner.with_atomic_for_cas();
+            }
             let client = RawClient {
-                inner: Arc::new(inner),
+                inner: Some(Arc::new(inner)),
+                atomic,
             };
             Python::with_gil(|py| PyCell::new(py, client).map(|py_cell| py_cell.to_object(py)))
         })
     }
 
+    /// Whether writes are executed in atomic mode, which `compare_and_swap`
+    /// requires and `delete_range` does not support.
+    #[getter]
+    pub fn atomic(&self) -> bool {
+        self.atomic
+    }
+
+    /// Returns a client sharing the same connection whose writes are executed
+    /// in atomic mode. Atomic and non-atomic writes to the same keys must not
+    /// be mixed.
+    pub fn with_atomic(&self) -> PyResult<RawClient> {
+        Ok(RawClient {
+            inner: Some(Arc::new(self.client()?.with_atomic_for_cas())),
+            atomic: true,
+        })
+    }
+
+    /// Releases the client. Its connections to PD and TiKV are torn down
+    /// once every client, transaction and snapshot sharing them is released,
+    /// and further calls on this client raise `ClientClosedError`.
+    pub fn close(&mut self) {
+        self.inner = None;
+    }
+
+    #[getter]
+    pub fn closed(&self) -> bool {
+        self.inner.is_none()
+    }
+
     #[pyo3(signature=(key, cf="default"))]
     pub fn get<'p>(&self, py: Python<'p>, key: Vec<u8>, cf: &str) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             let val: Option<Py<PyBytes>> = inner?
                 .get(key)
@@ -61,8 +110,7 @@ impl RawClient {
         keys: Vec<Vec<u8>>,
         cf: &str,
     ) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             let kvpairs = inner?.batch_get(keys).await.map_err(to_py_execption)?;
             let py_list = to_py_kv_list(kvpairs)?;
@@ -71,6 +119,7 @@ impl RawClient {
     }
 
     #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false, cf="default"))]
+    #[allow(clippy::too_many_arguments)]
     pub fn scan<'p>(
         &self,
         py: Python<'p>,
@@ -81,8 +130,7 @@ impl RawClient {
         include_end: bool,
         cf: &str,
     ) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             let range = to_bound_range(start, end, include_start, include_end);
             let kvpairs = inner?.scan(range, limit).await.map_err(to_py_execption)?;
@@ -92,6 +140,7 @@ impl RawClient {
     }
 
     #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false, cf="default"))]
+    #[allow(clippy::too_many_arguments)]
     pub fn scan_keys<'p>(
         &self,
         py: Python<'p>,
@@ -102,8 +151,7 @@ impl RawClient {
         include_end: bool,
         cf: &str,
     ) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             let range = to_bound_range(start, end, include_start, include_end);
             let keys = inner?
@@ -115,6 +163,161 @@ impl RawClient {
         })
     }
 
+    /// Scans each of `ranges`, given as `(start, end, include_start,
+    /// include_end)` tuples, concurrently and returns one list of pairs per
+    /// range.
+    #[pyo3(signature=(ranges, each_limit, cf="default"))]
+    pub fn batch_scan<'p>(
+        &self,
+        py: Python<'p>,
+        ranges: Vec<PyRange>,
+        each_limit: u32,
+        cf: &str,
+    ) -> PyResult<&'p PyAny> {
+        let inner = self.with_cf(cf);
+        future_into_py(py, async move {
+            let inner = inner?;
+            let results = try_join_all(
+                to_bound_ranges(ranges)
+                    .into_iter()
+                    .map(|range| inner.scan(range, each_limit)),
+            )
+            .await
+            .map_err(to_py_execption)?;
+            let py_lists = results
+                .into_iter()
+                .map(to_py_kv_list)
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(Python::with_gil(|py| py_lists.to_object(py)))
+        })
+    }
+
+    #[pyo3(signature=(ranges, each_limit, cf="default"))]
+    pub fn batch_scan_keys<'p>(
+        &self,
+        py: Python<'p>,
+        ranges: Vec<PyRange>,
+        each_limit: u32,
+        cf: &str,
+    ) -> PyResult<&'p PyAny> {
+        let inner = self.with_cf(cf);
+        future_into_py(py, async move {
+            let inner = inner?;
+            let results = try_join_all(
+                to_bound_ranges(ranges)
+                    .into_iter()
+                    .map(|range| inner.scan_keys(range, each_limit)),
+            )
+            .await
+            .map_err(to_py_execption)?;
+            let py_lists = results
+                .into_iter()
+                .map(to_py_key_list)
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(Python::with_gil(|py| py_lists.to_object(py)))
+        })
+    }
+
+    /// Returns `(checksum, total_kvs, total_bytes)` of the range, where
+    /// `checksum` is the XOR of the CRC64-XZ digests of each key followed by
+    /// its value.
+    ///
+    /// The range is scanned and digested in the client, without passing
+    /// values to Python, so the result differs from TiKV's checksum RPC and
+    /// can only be compared with another call to this method.
+    #[pyo3(signature=(start=None, end=None, include_start = true, include_end=false, cf="default"))]
+    pub fn checksum<'p>(
+        &self,
+        py: Python<'p>,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        cf: &str,
+    ) -> PyResult<&'p PyAny> {
+        let inner = self.with_cf(cf);
+        future_into_py(py, async move {
+            let inner = inner?;
+            let (mut start, mut include_start) = (start, include_start);
+            let (mut checksum, mut total_kvs, mut total_bytes) = (0u64, 0u64, 0u64);
+            loop {
+                let range = to_bound_range(start, end.clone(), include_start, include_end);
+                let pairs = inner
+                    .scan(range, CHECKSUM_BATCH_SIZE)
+                    .await
+                    .map_err(to_py_execption)?;
+                let exhausted = pairs.len() < CHECKSUM_BATCH_SIZE as usize;
+                start = pairs.last().map(|pair| pair.key().clone().into());
+                for pair in &pairs {
+                    let key: &[u8] = pair.key().into();
+                    let mut digest = CRC64.digest();
+                    digest.update(key);
+                    digest.update(pair.value());
+                    checksum ^= digest.finalize();
+                    total_kvs += 1;
+                    total_bytes += (key.len() + pair.value().len()) as u64;
+                }
+                if exhausted {
+                    break;
+                }
+                include_start = false;
+            }
+            Ok(Python::with_gil(|py| {
+                (checksum, total_kvs, total_bytes).to_object(py)
+            }))
+        })
+    }
+
+    /// Returns an async iterator over the range that fetches `batch_size`
+    /// pairs at a time.
+    #[pyo3(signature=(start=None, end=None, include_start = true, include_end=false, batch_size=1024, cf="default"))]
+    pub fn iter_scan(
+        &self,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        batch_size: u32,
+        cf: &str,
+    ) -> PyResult<ScanIterator> {
+        let inner = self.with_cf(cf)?;
+        ScanIterator::new(
+            ScanSource::Raw(inner),
+            start,
+            end,
+            include_start,
+            include_end,
+            batch_size,
+            false,
+            false,
+        )
+    }
+
+    /// Returns an async iterator over the keys in the range that fetches
+    /// `batch_size` keys at a time.
+    #[pyo3(signature=(start=None, end=None, include_start = true, include_end=false, batch_size=1024, cf="default"))]
+    pub fn iter_scan_keys(
+        &self,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        batch_size: u32,
+        cf: &str,
+    ) -> PyResult<ScanIterator> {
+        let inner = self.with_cf(cf)?;
+        ScanIterator::new(
+            ScanSource::Raw(inner),
+            start,
+            end,
+            include_start,
+            include_end,
+            batch_size,
+            true,
+            false,
+        )
+    }
+
     #[pyo3(signature=(key, value, cf="default"))]
     pub fn put<'p>(
         &self,
@@ -123,8 +326,7 @@ impl RawClient {
         value: Vec<u8>,
         cf: &str,
     ) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             inner?.put(key, value).await.map_err(to_py_execption)?;
             Ok(Python::with_gil(|py| py.None()))
@@ -138,8 +340,7 @@ impl RawClient {
         pairs: Py<PyDict>,
         cf: &str,
     ) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             let pairs = from_py_dict(pairs)?;
             inner?.batch_put(pairs).await.map_err(to_py_execption)?;
@@ -147,10 +348,32 @@ impl RawClient {
         })
     }
 
+    /// Sets the key to `new` if its current value is `expected`, where `None`
+    /// means the key does not exist. Returns the previous value and whether
+    /// the swap happened. Only available in atomic mode.
+    #[pyo3(signature=(key, expected, new, cf="default"))]
+    pub fn compare_and_swap<'p>(
+        &self,
+        py: Python<'p>,
+        key: Vec<u8>,
+        expected: Option<Vec<u8>>,
+        new: Vec<u8>,
+        cf: &str,
+    ) -> PyResult<&'p PyAny> {
+        let inner = self.with_cf(cf);
+        future_into_py(py, async move {
+            let (previous, swapped) = inner?
+                .compare_and_swap(key, expected, new)
+                .await
+                .map_err(to_py_execption)?;
+            let previous: Option<Py<PyBytes>> = previous.map(to_py_bytes);
+            Ok(Python::with_gil(|py| (previous, swapped).to_object(py)))
+        })
+    }
+
     #[pyo3(signature=(key, cf="default"))]
     pub fn delete<'p>(&self, py: Python<'p>, key: Vec<u8>, cf: &str) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             inner?.delete(key).await.map_err(to_py_execption)?;
             Ok(Python::with_gil(|py| py.None()))
@@ -164,8 +387,7 @@ impl RawClient {
         keys: Vec<Vec<u8>>,
         cf: &str,
     ) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             inner?.batch_delete(keys).await.map_err(to_py_execption)?;
             Ok(Python::with_gil(|py| py.None()))
@@ -182,12 +404,68 @@ impl RawClient {
         include_end: bool,
         cf: &str,
     ) -> PyResult<&'p PyAny> {
-        let inner: PyResult<tikv_client::RawClient> =
-            try { self.inner.with_cf(cf.try_into().map_err(to_py_execption)?) };
+        let inner = self.with_cf(cf);
         future_into_py(py, async move {
             let range = to_bound_range(start, end, include_start, include_end);
             inner?.delete_range(range).await.map_err(to_py_execption)?;
             Ok(Python::with_gil(|py| py.None()))
         })
     }
+
+    /// Deletes each of `ranges`, given as `(start, end, include_start,
+    /// include_end)` tuples, concurrently.
+    #[pyo3(signature=(ranges, cf="default"))]
+    pub fn batch_delete_range<'p>(
+        &self,
+        py: Python<'p>,
+        ranges: Vec<PyRange>,
+        cf: &str,
+    ) -> PyResult<&'p PyAny> {
+        let inner = self.with_cf(cf);
+        future_into_py(py, async move {
+            let inner = inner?;
+            try_join_all(
+                to_bound_ranges(ranges)
+                    .into_iter()
+                    .map(|range| inner.delete_range(range)),
+            )
+            .await
+            .map_err(to_py_execption)?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    /// Deletes all keys starting with `prefix`. An empty prefix is rejected;
+    /// use `delete_range()` to delete every key.
+    #[pyo3(signature=(prefix, cf="default"))]
+    pub fn delete_prefix<'p>(
+        &self,
+        py: Python<'p>,
+        prefix: Vec<u8>,
+        cf: &str,
+    ) -> PyResult<&'p PyAny> {
+        if prefix.is_empty() {
+            return Err(InvalidArgumentError::new_err("`prefix` must not be empty"));
+        }
+        let inner = self.with_cf(cf);
+        future_into_py(py, async move {
+            let end = prefix_end(&prefix);
+            let range = to_bound_range(Some(prefix), end, true, false);
+            inner?.delete_range(range).await.map_err(to_py_execption)?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+}
+
+impl RawClient {
+    fn client(&self) -> PyResult<&tikv_client::RawClient> {
+        self.inner
+            .as_deref()
+            .ok_or_else(|| ClientClosedError::new_err("the client has been closed"))
+    }
+
+    fn with_cf(&self, cf: &str) -> PyResult<tikv_client::RawClient> {
+        let cf = cf.try_into().map_err(to_py_execption)?;
+        Ok(self.client()?.with_cf(cf))
+    }
 }
@@ -0,0 +1,124 @@
+// Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
+
+use pyo3::basic::CompareOp;
+use pyo3::prelude::*;
+use tikv_client::TimestampExt as _;
+
+use crate::error::InvalidArgumentError;
+
+/// The number of bits of a version taken by the logical part.
+const LOGICAL_BITS: u32 = 18;
+
+/// A timestamp allocated by PD, made of the physical time in milliseconds
+/// since the Unix epoch and a logical counter.
+#[pyclass]
+#[derive(Clone)]
+pub struct Timestamp {
+    pub inner: tikv_client::Timestamp,
+}
+
+#[pymethods]
+impl Timestamp {
+    #[new]
+    #[pyo3(signature=(physical, logical=0))]
+    pub fn new(physical: i64, logical: i64) -> PyResult<Self> {
+        if !(0..1 << (63 - LOGICAL_BITS)).contains(&physical) {
+            return Err(InvalidArgumentError::new_err(
+                "`physical` must not be negative and must fit in 45 bits",
+            ));
+        }
+        if !(0..1 << LOGICAL_BITS).contains(&logical) {
+            return Err(InvalidArgumentError::new_err(
+                "`logical` must not be negative and must fit in 18 bits",
+            ));
+        }
+        Ok(Timestamp {
+            inner: tikv_client::Timestamp {
+                physical,
+                logical,
+                suffix_bits: 0,
+            },
+        })
+    }
+
+    #[staticmethod]
+    pub fn from_version(version: u64) -> Self {
+        Timestamp {
+            inner: tikv_client::Timestamp::from_version(version),
+        }
+    }
+
+    /// Converts an aware `datetime`, or a naive one in local time, with the
+    /// logical part set to 0.
+    #[staticmethod]
+    pub fn from_datetime(datetime: &PyAny) -> PyResult<Self> {
+        let seconds: f64 = datetime.call_method0("timestamp")?.extract()?;
+        Timestamp::new((seconds * 1000.0).round() as i64, 0)
+    }
+
+    /// Returns the physical part as an aware `datetime` in UTC.
+    pub fn to_datetime<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let datetime = py.import("datetime")?;
+        let utc = datetime.getattr("timezone")?.getattr("utc")?;
+        datetime
+            .getattr("datetime")?
+            .call_method1("fromtimestamp", (self.inner.physical as f64 / 1000.0, utc))
+    }
+
+    #[getter]
+    pub fn physical(&self) -> i64 {
+        self.inner.physical
+    }
+
+    #[getter]
+    pub fn logical(&self) -> i64 {
+        self.inner.logical
+    }
+
+    #[getter]
+    pub fn version(&self) -> u64 {
+        self.inner.version()
+    }
+
+    pub fn __int__(&self) -> u64 {
+        self.inner.version()
+    }
+
+    pub fn __index__(&self) -> u64 {
+        self.inner.version()
+    }
+
+    pub fn __hash__(&self) -> u64 {
+        self.inner.version()
+    }
+
+    pub fn __richcmp__(&self, other: &PyAny, op: CompareOp) -> PyResult<PyObject> {
+        let py = other.py();
+        match other.extract::<Version>() {
+            Ok(other) => Ok(op
+                .matches(self.version().cmp(&other.0.version()))
+                .into_py(py)),
+            Err(_) => Ok(py.NotImplemented()),
+        }
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "Timestamp(physical={}, logical={})",
+            self.inner.physical, self.inner.logical
+        )
+    }
+}
+
+/// A timestamp passed from Python as either a `Timestamp` or a version.
+pub struct Version(pub tikv_client::Timestamp);
+
+impl<'source> FromPyObject<'source> for Version {
+    fn extract(value: &'source PyAny) -> PyResult<Self> {
+        if let Ok(timestamp) = value.extract::<PyRef<Timestamp>>() {
+            return Ok(Version(timestamp.inner.clone()));
+        }
+        let version: u64 = value.extract()?;
+        Ok(Version(tikv_client::Timestamp::from_version(version)))
+    }
+}
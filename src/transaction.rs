@@ -1,65 +1,164 @@
 // Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
 
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::Duration;
 
 use pyo3::prelude::*;
 use pyo3::types::*;
 use pyo3::ToPyObject;
 use pyo3_asyncio::tokio::future_into_py;
+use pyo3_asyncio::tokio::get_runtime;
+use tikv_client::proto::kvrpcpb;
+use tikv_client::transaction::HeartbeatOption;
+use tikv_client::CheckLevel;
+use tikv_client::RetryOptions;
 use tikv_client::TimestampExt as _;
 use tikv_client::TransactionOptions;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 
+use crate::config::Backoff;
+use crate::config::Config;
+use crate::error::is_retryable_error;
+use crate::error::to_py_execption;
+use crate::error::with_key;
+use crate::error::ClientClosedError;
+use crate::error::InvalidArgumentError;
+use crate::error::TransactionError;
+use crate::iterator::ScanIterator;
+use crate::iterator::ScanSource;
+use crate::timestamp::Version;
 use crate::utils::*;
 
 #[pyclass]
 pub struct TransactionClient {
-    inner: Arc<tikv_client::TransactionClient>,
+    inner: Option<Arc<tikv_client::TransactionClient>>,
+    region_backoff: Option<tikv_client::Backoff>,
+    lock_backoff: Option<tikv_client::Backoff>,
 }
 
 #[pymethods]
 impl TransactionClient {
     #[classmethod]
+    #[pyo3(signature=(pd_endpoints, config=None))]
     pub fn connect<'p>(
         _cls: &PyType,
         py: Python<'p>,
         pd_endpoints: Vec<String>,
-        timeout: Option<f64>,
+        config: Option<Config>,
     ) -> PyResult<&'p PyAny> {
+        let config = config.unwrap_or_default();
+        let tikv_config = config.to_tikv_config()?;
         future_into_py(py, async move {
-            let mut config = tikv_client::Config::default();
-            if let Some(timeout) = timeout {
-                config = config.with_timeout(Duration::from_secs_f64(timeout));
-            }
-            let inner = tikv_client::TransactionClient::new_with_config(pd_endpoints, config)
+            let inner = tikv_client::TransactionClient::new_with_config(pd_endpoints, tikv_config)
                 .await
                 .map_err(to_py_execption)?;
             let client = TransactionClient {
-                inner: Arc::new(inner),
+                inner: Some(Arc::new(inner)),
+                region_backoff: config.region_backoff.map(|backoff| backoff.inner),
+                lock_backoff: config.lock_backoff.map(|backoff| backoff.inner),
             };
             Python::with_gil(|py| Ok(PyCell::new(py, client)?.to_object(py)))
         })
     }
 
+    /// Releases the client. Its connections to PD and TiKV are torn down
+    /// once every client, transaction and snapshot sharing them is released,
+    /// and further calls on this client raise `ClientClosedError`.
+    pub fn close(&mut self) {
+        self.inner = None;
+    }
+
+    #[getter]
+    pub fn closed(&self) -> bool {
+        self.inner.is_none()
+    }
+
     #[pyo3(signature=(pessimistic = false))]
     pub fn begin<'p>(&self, py: Python<'p>, pessimistic: bool) -> PyResult<&'p PyAny> {
-        let inner = self.inner.clone();
-        future_into_py(py, async move {
-            let transaction = if pessimistic {
-                inner.begin_pessimistic().await.map_err(to_py_execption)?
+        self.begin_transaction(
+            py,
+            self.transaction_options(pessimistic),
+            pessimistic,
+            false,
+        )
+    }
+
+    /// Begins a transaction with the given options. The backoffs default to
+    /// the ones of the client's config, `heartbeat_interval` is in seconds
+    /// with `0` disabling heartbeats, and `drop_check` is one of `"panic"`,
+    /// `"warn"` or `"none"`.
+    #[pyo3(signature=(
+        pessimistic=false,
+        async_commit=false,
+        try_one_pc=false,
+        read_only=false,
+        region_backoff=None,
+        lock_backoff=None,
+        heartbeat_interval=None,
+        drop_check=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn begin_with_options<'p>(
+        &self,
+        py: Python<'p>,
+        pessimistic: bool,
+        async_commit: bool,
+        try_one_pc: bool,
+        read_only: bool,
+        region_backoff: Option<Backoff>,
+        lock_backoff: Option<Backoff>,
+        heartbeat_interval: Option<f64>,
+        drop_check: Option<&str>,
+    ) -> PyResult<&'p PyAny> {
+        let mut options = if pessimistic {
+            TransactionOptions::new_pessimistic()
+        } else {
+            TransactionOptions::new_optimistic()
+        };
+        options = options.retry_options(self.retry_options(
+            pessimistic,
+            region_backoff.map(|backoff| backoff.inner),
+            lock_backoff.map(|backoff| backoff.inner),
+        ));
+        if async_commit {
+            options = options.use_async_commit();
+        }
+        if try_one_pc {
+            options = options.try_one_pc();
+        }
+        if read_only {
+            options = options.read_only();
+        }
+        if let Some(interval) = heartbeat_interval {
+            let interval = Duration::try_from_secs_f64(interval).map_err(|_| {
+                InvalidArgumentError::new_err("`heartbeat_interval` must be a non-negative number")
+            })?;
+            options = options.heartbeat_option(if interval.is_zero() {
+                HeartbeatOption::NoHeartbeat
             } else {
-                inner.begin_optimistic().await.map_err(to_py_execption)?
-            };
-            let transaction = Transaction {
-                inner: Arc::new(RwLock::new(transaction)),
-            };
-            Python::with_gil(|py| PyCell::new(py, transaction).map(|py_cell| py_cell.to_object(py)))
-        })
+                HeartbeatOption::FixedTime(interval)
+            });
+        }
+        if let Some(drop_check) = drop_check {
+            options = options.drop_check(match drop_check {
+                "panic" => CheckLevel::Panic,
+                "warn" => CheckLevel::Warn,
+                "none" => CheckLevel::None,
+                _ => {
+                    return Err(InvalidArgumentError::new_err(format!(
+                        "unknown drop check level `{}`",
+                        drop_check
+                    )))
+                }
+            });
+        }
+        self.begin_transaction(py, options, pessimistic, read_only)
     }
 
     pub fn current_timestamp<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
-        let inner = self.inner.clone();
+        let inner = self.client()?.clone();
         future_into_py(py, async move {
             let timestamp = inner
                 .current_timestamp()
@@ -70,30 +169,95 @@ impl TransactionClient {
         })
     }
 
-    #[pyo3(signature=(timestamp=0, pessimistic = false))]
-    pub fn snapshot(&self, timestamp: u64, pessimistic: bool) -> Snapshot {
-        Snapshot {
-            inner: Arc::new(RwLock::new(self.inner.snapshot(
-                tikv_client::Timestamp::from_version(timestamp),
-                if pessimistic {
-                    TransactionOptions::new_pessimistic()
-                } else {
-                    TransactionOptions::new_optimistic()
-                },
-            ))),
-        }
+    /// Returns a snapshot at `timestamp`, a `Timestamp` or a version.
+    #[pyo3(signature=(timestamp=Version(tikv_client::Timestamp::default()), pessimistic = false))]
+    pub fn snapshot(&self, timestamp: Version, pessimistic: bool) -> PyResult<Snapshot> {
+        Ok(Snapshot {
+            inner: Arc::new(RwLock::new(
+                self.client()?
+                    .snapshot(timestamp.0, self.transaction_options(pessimistic)),
+            )),
+        })
     }
 
-    pub fn gc<'p>(&self, py: Python<'p>, safepoint: u64) -> PyResult<&'p PyAny> {
-        let inner = self.inner.clone();
+    pub fn gc<'p>(&self, py: Python<'p>, safepoint: Version) -> PyResult<&'p PyAny> {
+        let inner = self.client()?.clone();
+        future_into_py(py, async move {
+            let result = inner.gc(safepoint.0).await.map_err(to_py_execption)?;
+            Ok(Python::with_gil(|py| result.to_object(py)))
+        })
+    }
+}
+
+impl TransactionClient {
+    fn client(&self) -> PyResult<&Arc<tikv_client::TransactionClient>> {
+        self.inner
+            .as_ref()
+            .ok_or_else(|| ClientClosedError::new_err("the client has been closed"))
+    }
+
+    fn begin_transaction<'p>(
+        &self,
+        py: Python<'p>,
+        options: TransactionOptions,
+        pessimistic: bool,
+        read_only: bool,
+    ) -> PyResult<&'p PyAny> {
+        let inner = self.client()?.clone();
         future_into_py(py, async move {
-            let result = inner
-                .gc(tikv_client::Timestamp::from_version(safepoint))
+            let transaction = inner
+                .begin_with_options(options)
                 .await
                 .map_err(to_py_execption)?;
-            Ok(Python::with_gil(|py| result.to_object(py)))
+            let transaction = Transaction {
+                start_ts: transaction.start_timestamp().version(),
+                inner: Arc::new(RwLock::new(transaction)),
+                pessimistic,
+                read_only,
+                state: Arc::new(Mutex::new(if read_only {
+                    TransactionState::ReadOnly
+                } else {
+                    TransactionState::Active
+                })),
+                heartbeat: Mutex::new(None),
+                heartbeat_error: Arc::new(Mutex::new(None)),
+            };
+            Python::with_gil(|py| PyCell::new(py, transaction).map(|py_cell| py_cell.to_object(py)))
         })
     }
+
+    /// The options `begin_optimistic()` and `begin_pessimistic()` would use,
+    /// with the backoffs from the client's config applied.
+    fn transaction_options(&self, pessimistic: bool) -> TransactionOptions {
+        let options = if pessimistic {
+            TransactionOptions::new_pessimistic()
+        } else {
+            TransactionOptions::new_optimistic()
+        };
+        options.retry_options(self.retry_options(pessimistic, None, None))
+    }
+
+    /// The default retry options, with the given backoffs, or else the ones
+    /// from the client's config, applied.
+    fn retry_options(
+        &self,
+        pessimistic: bool,
+        region_backoff: Option<tikv_client::Backoff>,
+        lock_backoff: Option<tikv_client::Backoff>,
+    ) -> RetryOptions {
+        let mut retry_options = if pessimistic {
+            RetryOptions::default_pessimistic()
+        } else {
+            RetryOptions::default_optimistic()
+        };
+        if let Some(backoff) = region_backoff.or_else(|| self.region_backoff.clone()) {
+            retry_options.region_backoff = backoff;
+        }
+        if let Some(backoff) = lock_backoff.or_else(|| self.lock_backoff.clone()) {
+            retry_options.lock_backoff = backoff;
+        }
+        retry_options
+    }
 }
 
 #[pyclass]
@@ -103,6 +267,54 @@ pub struct Snapshot {
 
 #[pymethods]
 impl Snapshot {
+    /// Returns an async iterator over the range that fetches `batch_size`
+    /// pairs at a time.
+    #[pyo3(signature=(start=None, end=None, include_start = true, include_end=false, batch_size=1024, reverse=false))]
+    pub fn iter_scan(
+        &self,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        batch_size: u32,
+        reverse: bool,
+    ) -> PyResult<ScanIterator> {
+        ScanIterator::new(
+            ScanSource::Snapshot(self.inner.clone()),
+            start,
+            end,
+            include_start,
+            include_end,
+            batch_size,
+            false,
+            reverse,
+        )
+    }
+
+    /// Returns an async iterator over the keys in the range that fetches
+    /// `batch_size` keys at a time.
+    #[pyo3(signature=(start=None, end=None, include_start = true, include_end=false, batch_size=1024, reverse=false))]
+    pub fn iter_scan_keys(
+        &self,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        batch_size: u32,
+        reverse: bool,
+    ) -> PyResult<ScanIterator> {
+        ScanIterator::new(
+            ScanSource::Snapshot(self.inner.clone()),
+            start,
+            end,
+            include_start,
+            include_end,
+            batch_size,
+            true,
+            reverse,
+        )
+    }
+
     pub fn get<'p>(&self, py: Python<'p>, key: Vec<u8>) -> PyResult<&'p PyAny> {
         let inner = self.inner.clone();
         future_into_py(py, async move {
@@ -144,7 +356,8 @@ impl Snapshot {
         })
     }
 
-    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false))]
+    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false, reverse=false))]
+    #[allow(clippy::too_many_arguments)]
     pub fn scan<'p>(
         &self,
         py: Python<'p>,
@@ -153,22 +366,32 @@ impl Snapshot {
         limit: u32,
         include_start: bool,
         include_end: bool,
+        reverse: bool,
     ) -> PyResult<&'p PyAny> {
         let inner = self.inner.clone();
         future_into_py(py, async move {
             let range = to_bound_range(start, end, include_start, include_end);
-            let kv_pairs = inner
-                .write()
-                .await
-                .scan(range, limit)
-                .await
-                .map_err(to_py_execption)?;
+            let mut inner = inner.write().await;
+            let kv_pairs: Vec<_> = if reverse {
+                inner
+                    .scan_reverse(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            } else {
+                inner
+                    .scan(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            };
             let py_list = to_py_kv_list(kv_pairs)?;
             Ok(Python::with_gil(|py| py_list.to_object(py)))
         })
     }
 
-    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false))]
+    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false, reverse=false))]
+    #[allow(clippy::too_many_arguments)]
     pub fn scan_keys<'p>(
         &self,
         py: Python<'p>,
@@ -177,29 +400,160 @@ impl Snapshot {
         limit: u32,
         include_start: bool,
         include_end: bool,
+        reverse: bool,
     ) -> PyResult<&'p PyAny> {
         let inner = self.inner.clone();
         future_into_py(py, async move {
             let range = to_bound_range(start, end, include_start, include_end);
-            let keys = inner
-                .write()
-                .await
-                .scan_keys(range, limit)
-                .await
-                .map_err(to_py_execption)?;
+            let mut inner = inner.write().await;
+            let keys: Vec<_> = if reverse {
+                inner
+                    .scan_keys_reverse(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            } else {
+                inner
+                    .scan_keys(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            };
             let py_list = to_py_key_list(keys)?;
             Ok(Python::with_gil(|py| py_list.to_object(py)))
         })
     }
 }
 
+#[derive(Clone, Copy)]
+enum TransactionState {
+    Active,
+    Committed,
+    RolledBack,
+    ReadOnly,
+}
+
 #[pyclass]
 pub struct Transaction {
     inner: Arc<RwLock<tikv_client::Transaction>>,
+    start_ts: u64,
+    pessimistic: bool,
+    read_only: bool,
+    state: Arc<Mutex<TransactionState>>,
+    heartbeat: Mutex<Option<JoinHandle<()>>>,
+    heartbeat_error: Arc<Mutex<Option<HeartbeatError>>>,
+}
+
+/// The last error of the background heartbeat, which stopped on it unless it
+/// was retryable.
+struct HeartbeatError {
+    err: PyErr,
+    stopped: bool,
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        self.stop_heartbeat();
+    }
+}
+
+impl Transaction {
+    fn stop_heartbeat(&self) {
+        if let Some(heartbeat) = self.heartbeat.lock().unwrap().take() {
+            heartbeat.abort();
+        }
+    }
 }
 
 #[pymethods]
 impl Transaction {
+    #[getter]
+    pub fn start_ts(&self) -> u64 {
+        self.start_ts
+    }
+
+    #[getter]
+    pub fn is_pessimistic(&self) -> bool {
+        self.pessimistic
+    }
+
+    #[getter]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// `"active"`, `"committed"`, `"rolled_back"` or `"read_only"`. A
+    /// transaction whose commit or rollback failed stays active. A read-only
+    /// transaction has nothing to commit or roll back and stays `"read_only"`.
+    #[getter]
+    pub fn state(&self) -> &'static str {
+        match *self.state.lock().unwrap() {
+            TransactionState::Active => "active",
+            TransactionState::Committed => "committed",
+            TransactionState::RolledBack => "rolled_back",
+            TransactionState::ReadOnly => "read_only",
+        }
+    }
+
+    /// The last error of the background heartbeat started by
+    /// `start_heartbeat()`, or `None`.
+    #[getter]
+    pub fn heartbeat_error(&self, py: Python) -> Option<PyObject> {
+        self.heartbeat_error
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|error| error.err.value(py).to_object(py))
+    }
+
+    /// Returns an async iterator over the range that fetches `batch_size`
+    /// pairs at a time.
+    #[pyo3(signature=(start=None, end=None, include_start = true, include_end=false, batch_size=1024, reverse=false))]
+    pub fn iter_scan(
+        &self,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        batch_size: u32,
+        reverse: bool,
+    ) -> PyResult<ScanIterator> {
+        ScanIterator::new(
+            ScanSource::Transaction(self.inner.clone()),
+            start,
+            end,
+            include_start,
+            include_end,
+            batch_size,
+            false,
+            reverse,
+        )
+    }
+
+    /// Returns an async iterator over the keys in the range that fetches
+    /// `batch_size` keys at a time.
+    #[pyo3(signature=(start=None, end=None, include_start = true, include_end=false, batch_size=1024, reverse=false))]
+    pub fn iter_scan_keys(
+        &self,
+        start: Option<Vec<u8>>,
+        end: Option<Vec<u8>>,
+        include_start: bool,
+        include_end: bool,
+        batch_size: u32,
+        reverse: bool,
+    ) -> PyResult<ScanIterator> {
+        ScanIterator::new(
+            ScanSource::Transaction(self.inner.clone()),
+            start,
+            end,
+            include_start,
+            include_end,
+            batch_size,
+            true,
+            reverse,
+        )
+    }
+
     pub fn get<'p>(&self, py: Python<'p>, key: Vec<u8>) -> PyResult<&'p PyAny> {
         let inner = self.inner.clone();
         future_into_py(py, async move {
@@ -273,7 +627,8 @@ impl Transaction {
         })
     }
 
-    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false))]
+    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false, reverse=false))]
+    #[allow(clippy::too_many_arguments)]
     pub fn scan<'p>(
         &self,
         py: Python<'p>,
@@ -282,22 +637,32 @@ impl Transaction {
         limit: u32,
         include_start: bool,
         include_end: bool,
+        reverse: bool,
     ) -> PyResult<&'p PyAny> {
         let inner = self.inner.clone();
         future_into_py(py, async move {
             let range = to_bound_range(start, end, include_start, include_end);
-            let kv_pairs = inner
-                .write()
-                .await
-                .scan(range, limit)
-                .await
-                .map_err(to_py_execption)?;
+            let mut inner = inner.write().await;
+            let kv_pairs: Vec<_> = if reverse {
+                inner
+                    .scan_reverse(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            } else {
+                inner
+                    .scan(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            };
             let py_list = to_py_kv_list(kv_pairs)?;
             Ok(Python::with_gil(|py| py_list.to_object(py)))
         })
     }
 
-    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false))]
+    #[pyo3(signature=(start=None, end=None, limit=0, include_start = true, include_end=false, reverse=false))]
+    #[allow(clippy::too_many_arguments)]
     pub fn scan_keys<'p>(
         &self,
         py: Python<'p>,
@@ -306,16 +671,25 @@ impl Transaction {
         limit: u32,
         include_start: bool,
         include_end: bool,
+        reverse: bool,
     ) -> PyResult<&'p PyAny> {
         let inner = self.inner.clone();
         future_into_py(py, async move {
             let range = to_bound_range(start, end, include_start, include_end);
-            let keys = inner
-                .write()
-                .await
-                .scan_keys(range, limit)
-                .await
-                .map_err(to_py_execption)?;
+            let mut inner = inner.write().await;
+            let keys: Vec<_> = if reverse {
+                inner
+                    .scan_keys_reverse(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            } else {
+                inner
+                    .scan_keys(range, limit)
+                    .await
+                    .map_err(to_py_execption)?
+                    .collect()
+            };
             let py_list = to_py_key_list(keys)?;
             Ok(Python::with_gil(|py| py_list.to_object(py)))
         })
@@ -353,9 +727,9 @@ impl Transaction {
             inner
                 .write()
                 .await
-                .insert(key, value)
+                .insert(key.clone(), value)
                 .await
-                .map_err(to_py_execption)?;
+                .map_err(|err| with_key(to_py_execption(err), &key))?;
             Ok(Python::with_gil(|py| py.None()))
         })
     }
@@ -373,22 +747,179 @@ impl Transaction {
         })
     }
 
-    fn commit<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+    /// Buffers a put of each pair, given as a dict or as `(key, value)`
+    /// tuples. In a pessimistic transaction all keys are locked with a single
+    /// request.
+    pub fn batch_put<'p>(&self, py: Python<'p>, pairs: &PyAny) -> PyResult<&'p PyAny> {
         let inner = self.inner.clone();
+        let mutations = from_py_pairs(pairs)?
+            .into_iter()
+            .map(|pair| {
+                let (key, value) = pair.into();
+                kvrpcpb::Mutation {
+                    op: kvrpcpb::Op::Put.into(),
+                    key: key.into(),
+                    value,
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
         future_into_py(py, async move {
-            let timestamp = inner
+            inner
+                .write()
+                .await
+                .batch_mutate(mutations)
+                .await
+                .map_err(to_py_execption)?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    /// Inserts each pair, given as a dict or as `(key, value)` tuples. If a
+    /// key already exists, the pairs before it remain inserted. In a
+    /// pessimistic transaction each key is locked with its own request.
+    pub fn batch_insert<'p>(&self, py: Python<'p>, pairs: &PyAny) -> PyResult<&'p PyAny> {
+        let inner = self.inner.clone();
+        let pairs = from_py_pairs(pairs)?;
+        future_into_py(py, async move {
+            let mut inner = inner.write().await;
+            for (key, value) in pairs.into_iter().map(Into::into) {
+                let key: Vec<u8> = key.into();
+                inner
+                    .insert(key.clone(), value)
+                    .await
+                    .map_err(|err| with_key(to_py_execption(err), &key))?;
+            }
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    /// Buffers a delete of each key. In a pessimistic transaction all keys
+    /// are locked with a single request.
+    pub fn batch_delete<'p>(&self, py: Python<'p>, keys: Vec<Vec<u8>>) -> PyResult<&'p PyAny> {
+        let inner = self.inner.clone();
+        let mutations = keys
+            .into_iter()
+            .map(|key| kvrpcpb::Mutation {
+                op: kvrpcpb::Op::Del.into(),
+                key,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        future_into_py(py, async move {
+            inner
                 .write()
                 .await
+                .batch_mutate(mutations)
+                .await
+                .map_err(to_py_execption)?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    /// Extends the TTL of the primary lock and returns the new TTL in
+    /// milliseconds. Fails if no key has been locked or prewritten yet.
+    pub fn send_heart_beat<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let inner = self.inner.clone();
+        future_into_py(py, async move {
+            let ttl = inner
+                .write()
+                .await
+                .send_heart_beat()
+                .await
+                .map_err(to_py_execption)?;
+            Ok(Python::with_gil(|py| ttl.to_object(py)))
+        })
+    }
+
+    /// Sends a heartbeat every `interval` seconds in the background until
+    /// `commit()` or `rollback()` is called, so that the locks of a
+    /// long-running transaction are not resolved by other transactions.
+    /// Heartbeats sent before the primary key is locked are skipped. A
+    /// retryable error is kept in `heartbeat_error` and the next heartbeat is
+    /// sent as usual; any other error stops the heartbeat and is raised by
+    /// `commit()`, which then rolls the transaction back.
+    #[pyo3(signature=(interval=10.0))]
+    pub fn start_heartbeat(&self, interval: f64) -> PyResult<()> {
+        let interval = Duration::try_from_secs_f64(interval)
+            .ok()
+            .filter(|interval| !interval.is_zero())
+            .ok_or_else(|| InvalidArgumentError::new_err("`interval` must be greater than 0"))?;
+        if !matches!(*self.state.lock().unwrap(), TransactionState::Active) {
+            return Err(TransactionError::new_err(
+                "cannot start the heartbeat of a finished transaction",
+            ));
+        }
+        let inner = self.inner.clone();
+        let heartbeat_error = self.heartbeat_error.clone();
+        let heartbeat = get_runtime().spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let err = match inner.write().await.send_heart_beat().await {
+                    Ok(_) | Err(tikv_client::Error::NoPrimaryKey) => continue,
+                    Err(err) => err,
+                };
+                let stopped = !is_retryable_error(&err);
+                *heartbeat_error.lock().unwrap() = Some(HeartbeatError {
+                    err: to_py_execption(err),
+                    stopped,
+                });
+                if stopped {
+                    break;
+                }
+            }
+        });
+        if let Some(previous) = self.heartbeat.lock().unwrap().replace(heartbeat) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Does nothing for a read-only transaction, which the Rust client
+    /// refuses to commit.
+    fn commit<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        self.stop_heartbeat();
+        if self.read_only {
+            return future_into_py(py, async { Ok(Python::with_gil(|py| py.None())) });
+        }
+        let heartbeat_error = self
+            .heartbeat_error
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|error| error.stopped)
+            .map(|error| error.err.clone_ref(py));
+        let inner = self.inner.clone();
+        let state = self.state.clone();
+        future_into_py(py, async move {
+            let mut inner = inner.write().await;
+            if let Some(err) = heartbeat_error {
+                // The locks may have expired while the heartbeat was down, so
+                // the transaction is rolled back instead of committed.
+                if inner.rollback().await.is_ok() {
+                    *state.lock().unwrap() = TransactionState::RolledBack;
+                }
+                return Err(err);
+            }
+            let timestamp = inner
                 .commit()
                 .await
                 .map_err(to_py_execption)?
                 .map(|v| v.version());
+            *state.lock().unwrap() = TransactionState::Committed;
             Ok(Python::with_gil(|py| timestamp.to_object(py)))
         })
     }
 
+    /// Does nothing for a read-only transaction, which the Rust client
+    /// refuses to roll back.
     fn rollback<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        self.stop_heartbeat();
+        if self.read_only {
+            return future_into_py(py, async { Ok(Python::with_gil(|py| py.None())) });
+        }
         let inner = self.inner.clone();
+        let state = self.state.clone();
         future_into_py(py, async move {
             inner
                 .write()
@@ -396,6 +927,7 @@ impl Transaction {
                 .rollback()
                 .await
                 .map_err(to_py_execption)?;
+            *state.lock().unwrap() = TransactionState::RolledBack;
             Ok(Python::with_gil(|py| py.None()))
         })
     }
@@ -2,14 +2,9 @@
 
 use std::ops::Bound;
 
-use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
 use pyo3::types::*;
 
-pub fn to_py_execption(err: impl std::fmt::Display) -> PyErr {
-    PyException::new_err(format!("{}", err))
-}
-
 // pub fn from_py_bytes(bytes: Py<PyBytes>) -> Vec<u8> {
 //     Python::with_gil(|py| bytes.as_ref(py).as_bytes().to_vec())
 // }
@@ -66,6 +61,20 @@ pub fn from_py_dict(dict: Py<PyDict>) -> PyResult<Vec<tikv_client::KvPair>> {
     })
 }
 
+/// Accepts either a dict or an iterable of `(key, value)` tuples.
+pub fn from_py_pairs(pairs: &PyAny) -> PyResult<Vec<tikv_client::KvPair>> {
+    if let Ok(dict) = pairs.downcast::<PyDict>() {
+        return from_py_dict(dict.into());
+    }
+    pairs
+        .iter()?
+        .map(|pair| {
+            let (key, value): (Vec<u8>, Vec<u8>) = pair?.extract()?;
+            Ok(tikv_client::KvPair::new(key, value))
+        })
+        .collect()
+}
+
 pub fn to_bound_range(
     start: Option<Vec<u8>>,
     end: Option<Vec<u8>>,
@@ -92,3 +101,28 @@ pub fn to_bound_range(
     };
     tikv_client::BoundRange::from((start_bound, end_bound))
 }
+
+/// A range passed from Python as `(start, end, include_start, include_end)`.
+pub type PyRange = (Option<Vec<u8>>, Option<Vec<u8>>, bool, bool);
+
+pub fn to_bound_ranges(ranges: Vec<PyRange>) -> Vec<tikv_client::BoundRange> {
+    ranges
+        .into_iter()
+        .map(|(start, end, include_start, include_end)| {
+            to_bound_range(start, end, include_start, include_end)
+        })
+        .collect()
+}
+
+/// Returns the smallest key greater than every key starting with `prefix`, or
+/// `None` if there is no such key, i.e. the prefix consists of 0xFF bytes only.
+pub fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
@@ -1,6 +1,10 @@
+import asyncio
+import datetime
+
 import pytest
 
-from tikv_client import RawClient
+from tikv_client import Config, RawClient, Timestamp
+from tikv_client import ClientClosedError, InvalidArgumentError, KeyExistsError, TiKVError, UnsupportedModeError, WriteConflictError
 from tikv_client.asynchronous import TransactionClient
 
 
@@ -28,22 +32,16 @@ def test_raw_client():
 async def test_async_raw_client():
     client = await TransactionClient.connect(["127.0.0.1:2379"])
 
-    txn = await client.begin(pessimistic=True)
-    try:
+    async with client.begin(pessimistic=True) as txn:
         await txn.put(b"k1", b"v1")
         await txn.put(b"k2", b"v2")
         await txn.put(b"k3", b"v3")
         await txn.put(b"k4", b"v4")
         await txn.put(b"k5", b"v5")
-    finally:
-        await txn.commit()
 
-    txn = await client.begin(pessimistic=True)
-    try:
+    async with client.begin(pessimistic=True) as txn:
         assert await txn.get(b"k3") == b"v3"
         assert await txn.batch_get([b"k1", b"k4"]), [b"v1", b"v4"]
-    finally:
-        await txn.commit()
 
     snapshot = client.snapshot(await client.current_timestamp(), pessimistic=True)
     assert await snapshot.get(b"k3") == b"v3"
@@ -56,3 +54,315 @@ async def test_async_raw_client():
         (b"k4", b"v4"),
         (b"k5", b"v5"),
     ]
+
+
+@pytest.mark.asyncio
+async def test_typed_exceptions():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    txn = await client.begin()
+    await txn.put(b"k1", b"v1")
+    with pytest.raises(KeyExistsError) as excinfo:
+        await txn.insert(b"k1", b"v1")
+    assert isinstance(excinfo.value, TiKVError)
+    await txn.rollback()
+
+
+@pytest.mark.asyncio
+async def test_write_conflict():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    txn1 = await client.begin()
+    txn2 = await client.begin()
+    await txn1.put(b"conflict", b"v1")
+    await txn2.put(b"conflict", b"v2")
+    await txn1.commit()
+    with pytest.raises(WriteConflictError) as excinfo:
+        await txn2.commit()
+    assert excinfo.value.key == b"conflict"
+    assert excinfo.value.start_ts == txn2.start_ts
+    assert excinfo.value.conflict_ts == txn1.start_ts
+    assert excinfo.value.conflict_commit_ts is not None
+    assert excinfo.value.is_retryable
+    assert not excinfo.value.is_undetermined
+
+
+def test_config():
+    config = Config.from_dict({"timeout": 5.0, "ca_path": None})
+    assert config.timeout == 5.0
+    assert config.ca_path is None
+
+    with pytest.raises(TiKVError):
+        Config.from_dict({"unknown": 1})
+
+    client = RawClient.connect(["127.0.0.1:2379"], config=config)
+    client.put(b"k1", b"v1")
+    assert client.get(b"k1") == b"v1"
+
+    with pytest.raises(InvalidArgumentError):
+        RawClient.connect(["127.0.0.1:2379"], timeout=float("nan"))
+
+
+def test_compare_and_swap():
+    client = RawClient.connect(["127.0.0.1:2379"])
+    with pytest.raises(UnsupportedModeError):
+        client.compare_and_swap(b"cas", None, b"v1")
+
+    client = client.with_atomic()
+    client.delete(b"cas")
+
+    assert client.compare_and_swap(b"cas", None, b"v1") == (None, True)
+    assert client.compare_and_swap(b"cas", b"v0", b"v2") == (b"v1", False)
+    assert client.compare_and_swap(b"cas", b"v1", b"v2") == (b"v1", True)
+
+
+def test_raw_iter_scan():
+    client = RawClient.connect(["127.0.0.1:2379"])
+    client.batch_put({b"iter%d" % i: b"v%d" % i for i in range(10)})
+
+    pairs = list(client.iter_scan(b"iter", b"iter9", include_end=True, batch_size=3))
+    assert pairs == [(b"iter%d" % i, b"v%d" % i) for i in range(10)]
+
+    keys = list(client.iter_scan_keys(b"iter", b"iter9", batch_size=4))
+    assert keys == [b"iter%d" % i for i in range(9)]
+
+
+@pytest.mark.asyncio
+async def test_transaction_iter_scan():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    txn = await client.begin()
+    for i in range(5):
+        await txn.put(b"titer%d" % i, b"v%d" % i)
+    await txn.commit()
+
+    txn = await client.begin()
+    await txn.put(b"titer5", b"v5")
+    await txn.delete(b"titer0")
+    keys = [key async for key in txn.iter_scan_keys(b"titer", b"titer9", batch_size=2)]
+    assert keys == [b"titer%d" % i for i in range(1, 6)]
+    await txn.rollback()
+
+    snapshot = client.snapshot(await client.current_timestamp(), pessimistic=False)
+    pairs = [pair async for pair in snapshot.iter_scan(b"titer", b"titer9", batch_size=2)]
+    assert pairs == [(b"titer%d" % i, b"v%d" % i) for i in range(5)]
+
+
+@pytest.mark.asyncio
+async def test_reverse_scan():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+    async with client.begin() as txn:
+        await txn.batch_put({b"rev%d" % i: b"v%d" % i for i in range(10)})
+
+    snapshot = client.snapshot(await client.current_timestamp(), pessimistic=False)
+    pairs = await snapshot.scan(b"rev", b"rev9", 3, include_end=True, reverse=True)
+    assert pairs == [(b"rev%d" % i, b"v%d" % i) for i in (9, 8, 7)]
+
+    keys = [key async for key in snapshot.iter_scan_keys(b"rev", b"rev9", batch_size=4, reverse=True)]
+    assert keys == [b"rev%d" % i for i in reversed(range(9))]
+
+
+def test_batch_scan():
+    client = RawClient.connect(["127.0.0.1:2379"])
+    client.batch_put({b"bscan%d" % i: b"v%d" % i for i in range(10)})
+
+    ranges = [(b"bscan0", b"bscan2", True, False), (b"bscan5", b"bscan7", True, True)]
+    assert client.batch_scan(ranges, 10) == [
+        [(b"bscan0", b"v0"), (b"bscan1", b"v1")],
+        [(b"bscan5", b"v5"), (b"bscan6", b"v6"), (b"bscan7", b"v7")],
+    ]
+    assert client.batch_scan_keys(ranges, 1) == [[b"bscan0"], [b"bscan5"]]
+
+
+def test_delete_prefix():
+    client = RawClient.connect(["127.0.0.1:2379"])
+    client.batch_put({b"dp\xff": b"v", b"dp\xff\xff": b"v", b"dq": b"v", b"\xff\xff": b"v"})
+
+    client.delete_prefix(b"dp\xff")
+    assert client.batch_get([b"dp\xff", b"dp\xff\xff", b"dq"]) == [(b"dq", b"v")]
+
+    client.delete_prefix(b"\xff")
+    assert client.get(b"\xff\xff") is None
+
+    client.batch_put({b"dr%d" % i: b"v" for i in range(6)})
+    client.batch_delete_range([(b"dr0", b"dr2", True, False), (b"dr3", b"dr4", True, True)])
+    assert client.scan_keys(b"dr", b"ds", 10) == [b"dr2", b"dr5"]
+
+
+def test_checksum():
+    client = RawClient.connect(["127.0.0.1:2379"])
+    client.delete_prefix(b"csum")
+    client.batch_put({b"csum1": b"v1", b"csum2": b"v22"})
+
+    checksum, total_kvs, total_bytes = client.checksum(b"csum", b"csun")
+    assert (total_kvs, total_bytes) == (2, 15)
+    assert checksum != 0
+    assert client.checksum(b"csum2", b"csun") == client.checksum(b"csum1", b"csun", include_start=False)
+
+
+def test_close():
+    with RawClient.connect(["127.0.0.1:2379"]) as client:
+        client.put(b"close", b"v")
+    assert client.closed
+    with pytest.raises(ClientClosedError):
+        client.get(b"close")
+
+
+@pytest.mark.asyncio
+async def test_async_close():
+    async with await TransactionClient.connect(["127.0.0.1:2379"]) as client:
+        await client.current_timestamp()
+    with pytest.raises(ClientClosedError):
+        await client.begin()
+
+
+@pytest.mark.asyncio
+async def test_transaction_context_manager():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    with pytest.raises(ValueError):
+        async with client.begin() as txn:
+            await txn.put(b"txn_ctx", b"v1")
+            raise ValueError()
+    assert txn.finished
+    async with client.begin() as txn:
+        assert await txn.get(b"txn_ctx") is None
+        await txn.put(b"txn_ctx", b"v2")
+    async with client.begin() as txn:
+        assert await txn.get(b"txn_ctx") == b"v2"
+        await txn.rollback()
+
+
+@pytest.mark.asyncio
+async def test_run_retries_write_conflict():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+    async with client.begin() as txn:
+        await txn.put(b"run_counter", b"0")
+    attempts = []
+
+    async def increment(txn):
+        attempts.append(txn)
+        value = int(await txn.get(b"run_counter"))
+        if len(attempts) == 1:
+            async with client.begin() as other:
+                await other.put(b"run_counter", b"%d" % (value + 10))
+        await txn.put(b"run_counter", b"%d" % (value + 1))
+        return value + 1
+
+    assert await client.run(increment) == 11
+    assert len(attempts) == 2
+
+
+@pytest.mark.asyncio
+async def test_run_does_not_retry_cancellation():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+    attempts = []
+
+    async def cancelled(txn):
+        attempts.append(txn)
+        await txn.put(b"run_cancelled", b"v")
+        raise asyncio.CancelledError()
+
+    with pytest.raises(asyncio.CancelledError):
+        await client.run(cancelled)
+    assert len(attempts) == 1
+
+
+@pytest.mark.asyncio
+async def test_begin_with_options():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    async with client.begin_with_options(async_commit=True, try_one_pc=True, drop_check="warn") as txn:
+        await txn.put(b"txn_options", b"v")
+
+    async with client.begin_with_options(read_only=True, heartbeat_interval=0) as txn:
+        assert await txn.get(b"txn_options") == b"v"
+
+    with pytest.raises(InvalidArgumentError):
+        await client.begin_with_options(drop_check="explode")
+    with pytest.raises(InvalidArgumentError):
+        await client.begin_with_options(heartbeat_interval=float("inf"))
+
+
+@pytest.mark.asyncio
+async def test_transaction_metadata():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    before = await client.current_timestamp()
+    txn = await client.begin(pessimistic=True)
+    assert txn.start_ts > before
+    assert txn.is_pessimistic
+    assert not txn.is_read_only
+    assert txn.state == "active"
+    await txn.commit()
+    assert txn.state == "committed"
+
+    txn = await client.begin_with_options(read_only=True)
+    assert txn.is_read_only
+    assert txn.state == "read_only"
+    assert txn.finished
+    await txn.rollback()
+    assert txn.state == "read_only"
+
+
+@pytest.mark.asyncio
+async def test_heartbeat():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    async with client.begin(pessimistic=True) as txn:
+        await txn.lock_keys([b"heartbeat"])
+        assert await txn.send_heart_beat() > 0
+        txn.start_heartbeat(interval=0.1)
+        await asyncio.sleep(0.5)
+        assert txn.heartbeat_error is None
+        await txn.put(b"heartbeat", b"v")
+    with pytest.raises(TiKVError):
+        txn.start_heartbeat()
+
+
+@pytest.mark.asyncio
+async def test_transaction_batch_writes():
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+
+    async with client.begin() as txn:
+        await txn.batch_put({b"tbatch1": b"v1", b"tbatch2": b"v2"})
+        await txn.batch_put([(b"tbatch3", b"v3")])
+        await txn.batch_delete([b"tbatch4"])
+
+    async with client.begin() as txn:
+        with pytest.raises(KeyExistsError) as excinfo:
+            await txn.batch_insert([(b"tbatch4", b"v4"), (b"tbatch5", b"v5"), (b"tbatch4", b"v4")])
+        assert excinfo.value.key == b"tbatch4"
+        await txn.rollback()
+
+    async with client.begin() as txn:
+        await txn.batch_delete([b"tbatch1", b"tbatch2"])
+        assert await txn.scan_keys(b"tbatch", b"tbatcj", 10) == [b"tbatch3"]
+
+    async with client.begin(pessimistic=True) as txn:
+        await txn.batch_put({b"tbatch1": b"v1", b"tbatch2": b"v2"})
+        await txn.batch_delete([b"tbatch3"])
+        assert await txn.scan_keys(b"tbatch", b"tbatcj", 10) == [b"tbatch1", b"tbatch2"]
+
+
+@pytest.mark.asyncio
+async def test_timestamp():
+    ts = Timestamp(1600000000000, 5)
+    assert (ts.physical, ts.logical) == (1600000000000, 5)
+    assert int(ts) == ts.version == (1600000000000 << 18) + 5
+    assert Timestamp.from_version(ts.version) == ts
+    assert ts < Timestamp(1600000000000, 6) and ts > ts.version - 1
+    assert ts.to_datetime() == datetime.datetime(2020, 9, 13, 12, 26, 40, tzinfo=datetime.timezone.utc)
+    assert Timestamp.from_datetime(ts.to_datetime()) == Timestamp(1600000000000)
+    with pytest.raises(InvalidArgumentError):
+        Timestamp(1 << 45)
+    with pytest.raises(InvalidArgumentError):
+        Timestamp(0, 1 << 18)
+
+    client = await TransactionClient.connect(["127.0.0.1:2379"])
+    async with client.begin() as txn:
+        await txn.put(b"time_travel", b"old")
+    before = Timestamp.from_version(await client.current_timestamp())
+    async with client.begin() as txn:
+        await txn.put(b"time_travel", b"new")
+    assert await client.snapshot(before, pessimistic=False).get(b"time_travel") == b"old"
@@ -1,7 +1,27 @@
 # Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
 
 import asyncio
+import time
 from . import asynchronous
+from .asynchronous import _retry_delay, _run_backoff
+from .tikv_client import (
+    Config,
+    Backoff,
+    Timestamp,
+    TiKVError,
+    TiKVKeyError,
+    WriteConflictError,
+    KeyExistsError,
+    DeadlockError,
+    RegionError,
+    RequestTimeoutError,
+    GrpcError,
+    UndeterminedError,
+    TransactionError,
+    UnsupportedModeError,
+    ClientClosedError,
+    InvalidArgumentError,
+)
 
 
 class RawClient:
@@ -9,14 +29,36 @@ class RawClient:
         raise Exception("Please use `RawClient.connect()` instead.")
 
     @classmethod
-    def connect(cls, pd_endpoints):
+    def connect(cls, pd_endpoints, timeout=None, ca_path=None, cert_path=None, key_path=None, config=None, atomic=False):
         event_loop = asyncio.get_event_loop()
         inner = event_loop.run_until_complete(
-            asynchronous.RawClient.connect(pd_endpoints))
+            asynchronous.RawClient.connect(pd_endpoints, timeout, ca_path, cert_path, key_path, config, atomic))
         self = cls.__new__(cls)
         self.inner = inner
         return self
 
+    @property
+    def closed(self):
+        return self.inner.closed
+
+    def close(self):
+        self.inner.close()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        self.close()
+
+    @property
+    def atomic(self):
+        return self.inner.atomic
+
+    def with_atomic(self):
+        client = RawClient.__new__(RawClient)
+        client.inner = self.inner.with_atomic()
+        return client
+
     def get(self, key, cf="default"):
         event_loop = asyncio.get_event_loop()
         return event_loop.run_until_complete(self.inner.get(key, cf))
@@ -33,6 +75,24 @@ class RawClient:
         event_loop = asyncio.get_event_loop()
         return event_loop.run_until_complete(self.inner.scan_keys(start, end, limit, include_start, include_end, cf))
 
+    def batch_scan(self, ranges, each_limit, cf="default"):
+        event_loop = asyncio.get_event_loop()
+        return event_loop.run_until_complete(self.inner.batch_scan(ranges, each_limit, cf))
+
+    def batch_scan_keys(self, ranges, each_limit, cf="default"):
+        event_loop = asyncio.get_event_loop()
+        return event_loop.run_until_complete(self.inner.batch_scan_keys(ranges, each_limit, cf))
+
+    def iter_scan(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, cf="default"):
+        return ScanIterator(self.inner.iter_scan(start, end, include_start, include_end, batch_size, cf))
+
+    def iter_scan_keys(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, cf="default"):
+        return ScanIterator(self.inner.iter_scan_keys(start, end, include_start, include_end, batch_size, cf))
+
+    def checksum(self, start=None, end=None, include_start=True, include_end=False, cf="default"):
+        event_loop = asyncio.get_event_loop()
+        return event_loop.run_until_complete(self.inner.checksum(start, end, include_start, include_end, cf))
+
     def put(self, key, value, cf="default"):
         event_loop = asyncio.get_event_loop()
         event_loop.run_until_complete(self.inner.put(key, value, cf))
@@ -41,6 +101,10 @@ class RawClient:
         event_loop = asyncio.get_event_loop()
         event_loop.run_until_complete(self.inner.batch_put(pairs, cf))
 
+    def compare_and_swap(self, key, expected, new, cf="default"):
+        event_loop = asyncio.get_event_loop()
+        return event_loop.run_until_complete(self.inner.compare_and_swap(key, expected, new, cf))
+
     def delete(self, key, cf="default"):
         event_loop = asyncio.get_event_loop()
         event_loop.run_until_complete(self.inner.delete(key, cf))
@@ -53,26 +117,112 @@ class RawClient:
         event_loop = asyncio.get_event_loop()
         return event_loop.run_until_complete(self.inner.delete_range(start, end, include_start, include_end, cf))
 
+    def batch_delete_range(self, ranges, cf="default"):
+        event_loop = asyncio.get_event_loop()
+        return event_loop.run_until_complete(self.inner.batch_delete_range(ranges, cf))
+
+    def delete_prefix(self, prefix, cf="default"):
+        event_loop = asyncio.get_event_loop()
+        return event_loop.run_until_complete(self.inner.delete_prefix(prefix, cf))
+
+
+class ScanIterator:
+    def __init__(self, inner):
+        self.inner = inner
+
+    def __iter__(self):
+        return self
+
+    def __next__(self):
+        event_loop = asyncio.get_event_loop()
+        try:
+            return event_loop.run_until_complete(_anext(self.inner))
+        except StopAsyncIteration:
+            raise StopIteration
+
+
+async def _anext(iterator):
+    return await iterator.__anext__()
+
 
 class TransactionClient:
     def __init__(self):
         raise Exception("Please use `TransactionClient.connect()` instead.")
 
     @classmethod
-    def connect(cls, pd_endpoints):
+    def connect(cls, pd_endpoints, timeout=None, ca_path=None, cert_path=None, key_path=None, config=None):
         event_loop = asyncio.get_event_loop()
         inner = event_loop.run_until_complete(
-            asynchronous.TransactionClient.connect(pd_endpoints))
+            asynchronous.TransactionClient.connect(pd_endpoints, timeout, ca_path, cert_path, key_path, config))
         self = cls.__new__(cls)
         self.inner = inner
         return self
 
+    @property
+    def closed(self):
+        return self.inner.closed
+
+    def close(self):
+        self.inner.close()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        self.close()
+
     def begin(self, pessimistic=False):
         event_loop = asyncio.get_event_loop()
         transaction = event_loop.run_until_complete(
             self.inner.begin(pessimistic))
         return Transaction(transaction)
 
+    def begin_with_options(self, pessimistic=False, async_commit=False, try_one_pc=False, read_only=False,
+                           region_backoff=None, lock_backoff=None, heartbeat_interval=None, drop_check=None):
+        event_loop = asyncio.get_event_loop()
+        transaction = event_loop.run_until_complete(self.inner.begin_with_options(
+            pessimistic, async_commit, try_one_pc, read_only,
+            region_backoff, lock_backoff, heartbeat_interval, drop_check))
+        return Transaction(transaction)
+
+    def run(self, fn, max_retries=10, backoff=None, pessimistic=False):
+        """Runs `fn(txn)` in a new transaction and commits it, unless `fn`
+        already did, and returns the result of `fn`.
+
+        If `fn` or the commit fails with a retryable error, e.g. a write
+        conflict, the transaction is rolled back and run again in a new one
+        after the delay given by `backoff`, at most `max_retries` times."""
+        backoff = _run_backoff(max_retries, backoff)
+        retries = 0
+        while True:
+            txn = self.begin(pessimistic)
+            try:
+                result = fn(txn)
+                if not txn.finished:
+                    txn.commit()
+                return result
+            except Exception as error:
+                undetermined = isinstance(error, TiKVError) and error.is_undetermined
+                if not txn.finished and not undetermined:
+                    try:
+                        txn.rollback()
+                    except TiKVError:
+                        pass
+                delay = _retry_delay(error, retries, max_retries, backoff)
+                if delay is None:
+                    raise
+            except BaseException:
+                # Roll back once on e.g. `KeyboardInterrupt`, which is never
+                # retried, so that the transaction is not dropped while active.
+                if not txn.finished:
+                    try:
+                        txn.rollback()
+                    except Exception:
+                        pass
+                raise
+            retries += 1
+            time.sleep(delay)
+
     def current_timestamp(self):
         event_loop = asyncio.get_event_loop()
         return event_loop.run_until_complete(
@@ -99,19 +249,58 @@ class Snapshot:
         event_loop = asyncio.get_event_loop()
         return event_loop.run_until_complete(self.inner.batch_get(keys))
 
-    def scan(self, start, end, limit, include_start=True, include_end=False):
+    def scan(self, start, end, limit, include_start=True, include_end=False, reverse=False):
         event_loop = asyncio.get_event_loop()
-        return event_loop.run_until_complete(self.inner.scan(start, end, limit, include_start, include_end))
+        return event_loop.run_until_complete(self.inner.scan(start, end, limit, include_start, include_end, reverse))
 
-    def scan_keys(self, start, end, limit, include_start=True, include_end=False):
+    def scan_keys(self, start, end, limit, include_start=True, include_end=False, reverse=False):
         event_loop = asyncio.get_event_loop()
-        return event_loop.run_until_complete(self.inner.scan_keys(start, end, limit, include_start, include_end))
+        return event_loop.run_until_complete(self.inner.scan_keys(start, end, limit, include_start, include_end, reverse))
+
+    def iter_scan(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return ScanIterator(self.inner.iter_scan(start, end, include_start, include_end, batch_size, reverse))
+
+    def iter_scan_keys(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return ScanIterator(self.inner.iter_scan_keys(start, end, include_start, include_end, batch_size, reverse))
 
 
 class Transaction:
     def __init__(self, inner):
         self.inner = inner
 
+    @property
+    def start_ts(self):
+        return self.inner.start_ts
+
+    @property
+    def is_pessimistic(self):
+        return self.inner.is_pessimistic
+
+    @property
+    def is_read_only(self):
+        return self.inner.is_read_only
+
+    @property
+    def state(self):
+        return self.inner.state
+
+    @property
+    def heartbeat_error(self):
+        return self.inner.heartbeat_error
+
+    @property
+    def finished(self):
+        return self.inner.finished
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_value, traceback):
+        """Commits the transaction if the block exits normally and rolls it
+        back if it raises, unless it has been committed or rolled back already."""
+        event_loop = asyncio.get_event_loop()
+        event_loop.run_until_complete(self.inner.__aexit__(exc_type, exc_value, traceback))
+
     def get(self, key):
         event_loop = asyncio.get_event_loop()
         return event_loop.run_until_complete(self.inner.get(key))
@@ -132,13 +321,19 @@ class Transaction:
         event_loop = asyncio.get_event_loop()
         return event_loop.run_until_complete(self.inner.batch_get_for_update(keys))
 
-    def scan(self, start, end, limit, include_start=True, include_end=False):
+    def scan(self, start, end, limit, include_start=True, include_end=False, reverse=False):
         event_loop = asyncio.get_event_loop()
-        return event_loop.run_until_complete(self.inner.scan(start, end, limit, include_start, include_end))
+        return event_loop.run_until_complete(self.inner.scan(start, end, limit, include_start, include_end, reverse))
 
-    def scan_keys(self, start, end, limit, include_start=True, include_end=False):
+    def scan_keys(self, start, end, limit, include_start=True, include_end=False, reverse=False):
         event_loop = asyncio.get_event_loop()
-        return event_loop.run_until_complete(self.inner.scan_keys(start, end, limit, include_start, include_end))
+        return event_loop.run_until_complete(self.inner.scan_keys(start, end, limit, include_start, include_end, reverse))
+
+    def iter_scan(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return ScanIterator(self.inner.iter_scan(start, end, include_start, include_end, batch_size, reverse))
+
+    def iter_scan_keys(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return ScanIterator(self.inner.iter_scan_keys(start, end, include_start, include_end, batch_size, reverse))
 
     def lock_keys(self, keys):
         event_loop = asyncio.get_event_loop()
@@ -156,6 +351,25 @@ class Transaction:
         event_loop = asyncio.get_event_loop()
         event_loop.run_until_complete(self.inner.delete(key))
 
+    def batch_put(self, pairs):
+        event_loop = asyncio.get_event_loop()
+        event_loop.run_until_complete(self.inner.batch_put(pairs))
+
+    def batch_insert(self, pairs):
+        event_loop = asyncio.get_event_loop()
+        event_loop.run_until_complete(self.inner.batch_insert(pairs))
+
+    def batch_delete(self, keys):
+        event_loop = asyncio.get_event_loop()
+        event_loop.run_until_complete(self.inner.batch_delete(keys))
+
+    def send_heart_beat(self):
+        event_loop = asyncio.get_event_loop()
+        return event_loop.run_until_complete(self.inner.send_heart_beat())
+
+    def start_heartbeat(self, interval=10.0):
+        self.inner.start_heartbeat(interval)
+
     def commit(self):
         event_loop = asyncio.get_event_loop()
         event_loop.run_until_complete(self.inner.commit())
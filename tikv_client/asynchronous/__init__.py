@@ -1,6 +1,61 @@
 # Copyright 2020 TiKV Project Authors. Licensed under Apache-2.0.
 
+import asyncio
+import copy
+
 from .. import tikv_client
+from ..tikv_client import (
+    Config,
+    Backoff,
+    Timestamp,
+    TiKVError,
+    TiKVKeyError,
+    WriteConflictError,
+    KeyExistsError,
+    DeadlockError,
+    RegionError,
+    RequestTimeoutError,
+    GrpcError,
+    UndeterminedError,
+    TransactionError,
+    UnsupportedModeError,
+    ClientClosedError,
+    InvalidArgumentError,
+)
+
+
+def _make_config(config, **options):
+    if config is None:
+        return Config(**options)
+    if isinstance(config, dict):
+        config = Config.from_dict(config)
+    if any(value is not None for value in options.values()):
+        raise Exception("Please set options either in `config` or as keyword arguments, not both.")
+    return config
+
+
+def _retry_delay(error, retries, max_retries, backoff):
+    """Returns how long to wait before running a failed transaction again, or
+    `None` if it must not be retried. Undetermined commits are never retried
+    as the transaction may have been committed."""
+    if not isinstance(error, TiKVError) or not error.is_retryable or error.is_undetermined:
+        return None
+    if retries >= max_retries:
+        return None
+    return backoff.next_delay()
+
+
+def _rollback_in_background(txn):
+    """Starts rolling back the transaction and ignores how it ends, as TiKV
+    resolves the locks of an abandoned transaction anyway."""
+    future = asyncio.ensure_future(txn.rollback())
+    future.add_done_callback(lambda future: future.cancelled() or future.exception())
+
+
+def _run_backoff(max_retries, backoff):
+    if backoff is None:
+        return Backoff.full_jitter(10, 1000, max_retries)
+    return copy.copy(backoff)
 
 
 class RawClient:
@@ -8,14 +63,41 @@ class RawClient:
         raise Exception("Please use `RawClient.connect()` instead.")
 
     @classmethod
-    async def connect(cls, pd_endpoints):
+    async def connect(cls, pd_endpoints, timeout=None, ca_path=None, cert_path=None, key_path=None, config=None, atomic=False):
         if not isinstance(pd_endpoints, list):
             raise Exception("Please use list as pd_endpoints. For example: `RawClient.connect([127.0.0.1:2379])`.")
-        inner = await tikv_client.RawClient.connect(pd_endpoints)
+        config = _make_config(config, timeout=timeout, ca_path=ca_path,
+                              cert_path=cert_path, key_path=key_path)
+        inner = await tikv_client.RawClient.connect(pd_endpoints, config, atomic)
         self = cls.__new__(cls)
         self.inner = inner
         return self
 
+    @property
+    def closed(self):
+        return self.inner.closed
+
+    def close(self):
+        self.inner.close()
+
+    async def aclose(self):
+        self.inner.close()
+
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, exc_type, exc_value, traceback):
+        await self.aclose()
+
+    @property
+    def atomic(self):
+        return self.inner.atomic
+
+    def with_atomic(self):
+        client = RawClient.__new__(RawClient)
+        client.inner = self.inner.with_atomic()
+        return client
+
     async def get(self, key, cf="default"):
         return await self.inner.get(key, cf)
 
@@ -28,12 +110,30 @@ class RawClient:
     async def scan_keys(self, start, end, limit, include_start=True, include_end=False, cf="default"):
         return await self.inner.scan_keys(start, end, limit, include_start, include_end, cf)
 
+    async def batch_scan(self, ranges, each_limit, cf="default"):
+        return await self.inner.batch_scan(ranges, each_limit, cf)
+
+    async def batch_scan_keys(self, ranges, each_limit, cf="default"):
+        return await self.inner.batch_scan_keys(ranges, each_limit, cf)
+
+    def iter_scan(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, cf="default"):
+        return self.inner.iter_scan(start, end, include_start, include_end, batch_size, cf)
+
+    def iter_scan_keys(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, cf="default"):
+        return self.inner.iter_scan_keys(start, end, include_start, include_end, batch_size, cf)
+
+    async def checksum(self, start=None, end=None, include_start=True, include_end=False, cf="default"):
+        return await self.inner.checksum(start, end, include_start, include_end, cf)
+
     async def put(self, key, value, cf="default"):
         await self.inner.put(key, value, cf)
 
     async def batch_put(self, pairs, cf="default"):
         await self.inner.batch_put(pairs, cf)
 
+    async def compare_and_swap(self, key, expected, new, cf="default"):
+        return await self.inner.compare_and_swap(key, expected, new, cf)
+
     async def delete(self, key, cf="default"):
         await self.inner.delete(key, cf)
 
@@ -43,24 +143,96 @@ class RawClient:
     async def delete_range(self, start, end=None, include_start=True, include_end=False, cf="default"):
         return await self.inner.delete_range(start, end, include_start, include_end, cf)
 
+    async def batch_delete_range(self, ranges, cf="default"):
+        return await self.inner.batch_delete_range(ranges, cf)
+
+    async def delete_prefix(self, prefix, cf="default"):
+        return await self.inner.delete_prefix(prefix, cf)
+
 
 class TransactionClient:
     def __init__(self):
         raise Exception("Please use `TransactionClient.connect()` instead.")
 
     @classmethod
-    async def connect(cls, pd_endpoints):
+    async def connect(cls, pd_endpoints, timeout=None, ca_path=None, cert_path=None, key_path=None, config=None):
         if not isinstance(pd_endpoints, list):
             raise Exception("Please use list as pd_endpoints. For example: `TransactionClient.connect([127.0.0.1:2379])`.")
-        inner = await tikv_client.TransactionClient.connect(pd_endpoints)
+        config = _make_config(config, timeout=timeout, ca_path=ca_path,
+                              cert_path=cert_path, key_path=key_path)
+        inner = await tikv_client.TransactionClient.connect(pd_endpoints, config)
         self = cls.__new__(cls)
         self.inner = inner
         return self
 
-    async def begin(self, pessimistic=False):
+    @property
+    def closed(self):
+        return self.inner.closed
+
+    def close(self):
+        self.inner.close()
+
+    async def aclose(self):
+        self.inner.close()
+
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, exc_type, exc_value, traceback):
+        await self.aclose()
+
+    def begin(self, pessimistic=False):
+        return _BeginTransaction(self._begin(pessimistic))
+
+    def begin_with_options(self, pessimistic=False, async_commit=False, try_one_pc=False, read_only=False,
+                           region_backoff=None, lock_backoff=None, heartbeat_interval=None, drop_check=None):
+        return _BeginTransaction(self._begin_with_options(
+            pessimistic, async_commit, try_one_pc, read_only,
+            region_backoff, lock_backoff, heartbeat_interval, drop_check))
+
+    async def _begin(self, pessimistic):
         transaction = await self.inner.begin(pessimistic)
         return Transaction(transaction)
 
+    async def _begin_with_options(self, *options):
+        transaction = await self.inner.begin_with_options(*options)
+        return Transaction(transaction)
+
+    async def run(self, fn, max_retries=10, backoff=None, pessimistic=False):
+        """Runs `await fn(txn)` in a new transaction and commits it, unless `fn`
+        already did, and returns the result of `fn`.
+
+        If `fn` or the commit fails with a retryable error, e.g. a write
+        conflict, the transaction is rolled back and run again in a new one
+        after the delay given by `backoff`, at most `max_retries` times."""
+        backoff = _run_backoff(max_retries, backoff)
+        retries = 0
+        while True:
+            txn = await self.begin(pessimistic)
+            try:
+                result = await fn(txn)
+                if not txn.finished:
+                    await txn.commit()
+                return result
+            except Exception as error:
+                undetermined = isinstance(error, TiKVError) and error.is_undetermined
+                if not txn.finished and not undetermined:
+                    try:
+                        await txn.rollback()
+                    except TiKVError:
+                        pass
+                delay = _retry_delay(error, retries, max_retries, backoff)
+                if delay is None:
+                    raise
+            except BaseException:
+                # The task is being cancelled or the interpreter is exiting, so
+                # the rollback is started without waiting for it.
+                if not txn.finished:
+                    _rollback_in_background(txn)
+                raise
+            retries += 1
+            await asyncio.sleep(delay)
+
     async def current_timestamp(self):
         return await self.inner.current_timestamp()
 
@@ -82,17 +254,84 @@ class Snapshot:
     async def batch_get(self, keys):
         return await self.inner.batch_get(keys)
 
-    async def scan(self, start, end, limit, include_start=True, include_end=False):
-        return await self.inner.scan(start, end, limit, include_start, include_end)
+    async def scan(self, start, end, limit, include_start=True, include_end=False, reverse=False):
+        return await self.inner.scan(start, end, limit, include_start, include_end, reverse)
+
+    async def scan_keys(self, start, end, limit, include_start=True, include_end=False, reverse=False):
+        return await self.inner.scan_keys(start, end, limit, include_start, include_end, reverse)
+
+    def iter_scan(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return self.inner.iter_scan(start, end, include_start, include_end, batch_size, reverse)
+
+    def iter_scan_keys(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return self.inner.iter_scan_keys(start, end, include_start, include_end, batch_size, reverse)
+
+
+class _BeginTransaction:
+    """Returned by `TransactionClient.begin()`, which can either be awaited or
+    used as `async with client.begin() as txn:`."""
 
-    async def scan_keys(self, start, end, limit, include_start=True, include_end=False):
-        return await self.inner.scan_keys(start, end, limit, include_start, include_end)
+    def __init__(self, begin):
+        self.begin = begin
+        self.transaction = None
+
+    def __await__(self):
+        return self.begin.__await__()
+
+    async def __aenter__(self):
+        self.transaction = await self.begin
+        return await self.transaction.__aenter__()
+
+    async def __aexit__(self, exc_type, exc_value, traceback):
+        return await self.transaction.__aexit__(exc_type, exc_value, traceback)
 
 
 class Transaction:
     def __init__(self, inner):
         self.inner = inner
 
+    @property
+    def start_ts(self):
+        return self.inner.start_ts
+
+    @property
+    def is_pessimistic(self):
+        return self.inner.is_pessimistic
+
+    @property
+    def is_read_only(self):
+        return self.inner.is_read_only
+
+    @property
+    def state(self):
+        return self.inner.state
+
+    @property
+    def heartbeat_error(self):
+        return self.inner.heartbeat_error
+
+    @property
+    def finished(self):
+        return self.inner.state != "active"
+
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, exc_type, exc_value, traceback):
+        """Commits the transaction if the block exits normally and rolls it
+        back if it raises, unless it has been committed or rolled back already."""
+        if self.finished:
+            return
+        if exc_type is None:
+            await self.commit()
+            return
+        try:
+            await self.rollback()
+        except TiKVError:
+            # The exception raised in the block is more useful than the
+            # rollback failure, and the locks are resolved by TiKV anyway.
+            pass
+
     async def get(self, key):
         return await self.inner.get(key)
 
@@ -108,11 +347,17 @@ class Transaction:
     async def batch_get_for_update(self, keys):
         return await self.inner.batch_get_for_update(keys)
 
-    async def scan(self, start, end, limit, include_start=True, include_end=False):
-        return await self.inner.scan(start, end, limit, include_start, include_end)
+    async def scan(self, start, end, limit, include_start=True, include_end=False, reverse=False):
+        return await self.inner.scan(start, end, limit, include_start, include_end, reverse)
+
+    async def scan_keys(self, start, end, limit, include_start=True, include_end=False, reverse=False):
+        return await self.inner.scan_keys(start, end, limit, include_start, include_end, reverse)
 
-    async def scan_keys(self, start, end, limit, include_start=True, include_end=False):
-        return await self.inner.scan_keys(start, end, limit, include_start, include_end)
+    def iter_scan(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return self.inner.iter_scan(start, end, include_start, include_end, batch_size, reverse)
+
+    def iter_scan_keys(self, start=None, end=None, include_start=True, include_end=False, batch_size=1024, reverse=False):
+        return self.inner.iter_scan_keys(start, end, include_start, include_end, batch_size, reverse)
 
     async def lock_keys(self, keys):
         await self.inner.lock_keys(keys)
@@ -126,6 +371,21 @@ class Transaction:
     async def delete(self, key):
         await self.inner.delete(key)
 
+    async def batch_put(self, pairs):
+        await self.inner.batch_put(pairs)
+
+    async def batch_insert(self, pairs):
+        await self.inner.batch_insert(pairs)
+
+    async def batch_delete(self, keys):
+        await self.inner.batch_delete(keys)
+
+    async def send_heart_beat(self):
+        return await self.inner.send_heart_beat()
+
+    def start_heartbeat(self, interval=10.0):
+        self.inner.start_heartbeat(interval)
+
     async def commit(self):
         await self.inner.commit()
 